@@ -0,0 +1,111 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use trie_rcv::{EliminationStrategies, RankedChoiceVoteTrie, TrieTabulation};
+use trie_rcv::arena::ArenaVoteTrie;
+use trie_rcv::vote::RankedVote;
+
+// tracks the number of live heap bytes so that the memory used by
+// each trie representation can be reported alongside its timings
+struct CountingAllocator;
+
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const NUM_CANDIDATES: u32 = 12;
+const NUM_VOTES: usize = 50_000;
+
+fn generate_votes() -> Vec<RankedVote> {
+    // deterministic pseudo-random ballots of varying lengths
+    let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+    let mut next_random = move || {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        seed
+    };
+
+    (0..NUM_VOTES).map(|_| {
+        let mut candidates: Vec<i32> = (1..=NUM_CANDIDATES as i32).collect();
+        let num_rankings = 1 + (next_random() % 6) as usize;
+        let mut rankings = Vec::new();
+        for _ in 0..num_rankings {
+            let index = (next_random() % candidates.len() as u64) as usize;
+            rankings.push(candidates.swap_remove(index));
+        }
+        RankedVote::from_vector(&rankings).unwrap()
+    }).collect()
+}
+
+fn measure_heap_bytes<T>(build: impl FnOnce() -> T) -> (T, usize) {
+    let bytes_before = ALLOCATED_BYTES.load(Ordering::Relaxed);
+    let built = build();
+    let bytes_after = ALLOCATED_BYTES.load(Ordering::Relaxed);
+    (built, bytes_after.saturating_sub(bytes_before))
+}
+
+fn build_trie(votes: &[RankedVote]) -> RankedChoiceVoteTrie {
+    let mut rcv = RankedChoiceVoteTrie::new();
+    rcv.set_elimination_strategy(EliminationStrategies::RankedPairs);
+    rcv.insert_votes(votes.to_vec());
+    rcv
+}
+
+fn build_arena_trie(votes: &[RankedVote]) -> ArenaVoteTrie {
+    let mut rcv = ArenaVoteTrie::new();
+    rcv.set_elimination_strategy(EliminationStrategies::RankedPairs);
+    rcv.insert_votes(votes.to_vec());
+    rcv
+}
+
+fn bench_trie_representations(criterion: &mut Criterion) {
+    let votes = generate_votes();
+
+    let (rcv, trie_bytes) = measure_heap_bytes(|| build_trie(&votes));
+    let (arena_rcv, arena_bytes) =
+        measure_heap_bytes(|| build_arena_trie(&votes));
+    println!(
+        "heap usage for {} votes: hash map trie {} bytes, arena trie {} bytes \
+        ({} nodes)", NUM_VOTES, trie_bytes, arena_bytes,
+        arena_rcv.get_num_nodes()
+    );
+
+    let mut group = criterion.benchmark_group("insert_votes");
+    group.bench_function("hash_map_trie", |bencher| {
+        bencher.iter(|| build_trie(black_box(&votes)))
+    });
+    group.bench_function("arena_trie", |bencher| {
+        bencher.iter(|| build_arena_trie(black_box(&votes)))
+    });
+    group.finish();
+
+    let mut group = criterion.benchmark_group("tabulate");
+    group.bench_function("hash_map_trie", |bencher| {
+        bencher.iter(|| black_box(&rcv).tabulate())
+    });
+    group.bench_function("arena_trie", |bencher| {
+        bencher.iter(|| black_box(&arena_rcv).tabulate())
+    });
+    let frozen_rcv = rcv.freeze();
+    group.bench_function("frozen_trie", |bencher| {
+        bencher.iter(|| black_box(&frozen_rcv).tabulate())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_trie_representations);
+criterion_main!(benches);
@@ -0,0 +1,285 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::iter::Sum;
+use smallvec::SmallVec;
+use crate::{CandidateId, EliminationStrategies, TrieTabulation};
+use crate::scoring::{
+    add_rank_count, build_rank_histogram, dowdall_weight, merge_rank_counts,
+    positional_scores, BigRational, CandidateRankCounts
+};
+use crate::tabulation::{TabulationSource, Tabulator};
+use crate::traversal::TrieTraversal;
+use crate::vote::{GenericRankedVote, GenericVoteValues};
+
+const ROOT: u32 = 0;
+
+// vote value and node index of each child, sorted by vote value.
+// Most nodes in a ballot trie have a single child, which is kept inline
+type ArenaChildren<C> = SmallVec<[(GenericVoteValues<C>, u32); 1]>;
+
+#[derive(Clone, Debug)]
+pub struct ArenaNode<C> {
+    num_votes: u64,
+    children: ArenaChildren<C>
+}
+
+impl<C: CandidateId> ArenaNode<C> {
+    fn new() -> Self {
+        ArenaNode { num_votes: 0, children: SmallVec::new() }
+    }
+
+    pub fn get_num_votes(&self) -> u64 {
+        self.num_votes
+    }
+
+    fn search_child(
+        &self, vote_value: &GenericVoteValues<C>
+    ) -> Result<usize, usize> {
+        // position of the child, or where it should be inserted
+        self.children.binary_search_by(
+            |(child_value, _)| child_value.cmp(vote_value)
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct GenericArenaVoteTrie<C> {
+    /*
+    ballot trie with the same interface as GenericRankedChoiceVoteTrie,
+    but with all nodes stored in a single flat vector. Each node keeps
+    a small sorted list of its children's indexes (searched by binary
+    search) instead of owning a hash map of its children, which uses far
+    less memory for the deep and sparse tries that ranked ballots
+    tend to produce
+    */
+    nodes: Vec<ArenaNode<C>>,
+    pub(crate) rank_counts: HashMap<C, Vec<u64>>,
+    pub(crate) elimination_strategy: EliminationStrategies,
+    pub(crate) unique_candidates: HashSet<C>,
+    pub(crate) declared_candidates: HashSet<C>,
+    pub(crate) excluded_candidates: HashSet<C>
+}
+
+pub type ArenaVoteTrie = GenericArenaVoteTrie<u32>;
+
+impl<C: CandidateId> Default for GenericArenaVoteTrie<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: CandidateId> GenericArenaVoteTrie<C> {
+    pub fn new() -> Self {
+        GenericArenaVoteTrie {
+            nodes: vec![ArenaNode::new()],
+            rank_counts: Default::default(),
+            elimination_strategy: EliminationStrategies::DowdallScoring,
+            unique_candidates: Default::default(),
+            declared_candidates: Default::default(),
+            excluded_candidates: Default::default(),
+        }
+    }
+
+    pub fn get_num_votes(&self) -> u64 {
+        self.nodes[ROOT as usize].num_votes
+    }
+
+    pub fn get_num_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn declare_candidates(&mut self, candidates: &[C]) {
+        for candidate in candidates {
+            self.unique_candidates.insert(candidate.clone());
+            self.declared_candidates.insert(candidate.clone());
+        }
+    }
+
+    pub fn merge(&mut self, other: GenericArenaVoteTrie<C>) {
+        /*
+        adds all the ballots counted in other into this trie. Settings
+        (elimination strategy and excluded candidates) are kept as is
+        */
+        self.add_nodes(&other);
+        for (candidate, rank_counts) in &other.rank_counts {
+            merge_rank_counts(
+                self.rank_counts.entry(candidate.clone()).or_default(),
+                rank_counts
+            );
+        }
+        self.unique_candidates.extend(other.unique_candidates);
+        self.declared_candidates.extend(other.declared_candidates);
+    }
+
+    pub(crate) fn add_nodes<T: TrieTraversal<C>>(&mut self, other: &T) {
+        // adds the vote counts of every node in other (which can be
+        // laid out differently) to the matching nodes of this trie
+        self.add_node(ROOT, other, other.root_node());
+    }
+
+    fn add_node<'a, T: TrieTraversal<C>>(
+        &mut self, node: u32, other: &'a T, other_node: T::Node<'a>
+    ) {
+        self.nodes[node as usize].num_votes += other.node_votes(other_node);
+        other.visit_children(other_node, |vote_value, other_child| {
+            let child = self.search_or_create_child(node, vote_value.clone());
+            self.add_node(child, other, other_child);
+        });
+    }
+
+    pub fn insert_votes(&mut self, votes: Vec<GenericRankedVote<C>>) {
+        for vote in votes {
+            self.insert_vote(vote);
+        }
+    }
+
+    pub fn insert_vote(&mut self, vote: GenericRankedVote<C>) {
+        self.insert_weighted_vote(vote, 1);
+    }
+
+    pub fn insert_weighted_vote(
+        &mut self, vote: GenericRankedVote<C>, num_votes: u64
+    ) {
+        if num_votes == 0 { return }
+        self.nodes[ROOT as usize].num_votes += num_votes;
+        let mut current = ROOT;
+
+        for (ranking, vote_value) in vote.iter().enumerate() {
+            if let GenericVoteValues::Candidate(candidate) = &vote_value {
+                self.unique_candidates.insert(candidate.clone());
+                add_rank_count(
+                    self.rank_counts.entry(candidate.clone()).or_default(),
+                    ranking, num_votes
+                );
+            }
+            current = self.search_or_create_child(current, vote_value);
+            self.nodes[current as usize].num_votes += num_votes;
+        }
+    }
+
+    pub fn search_nodes(
+        &self, ranked_vote: GenericRankedVote<C>
+    ) -> Option<Vec<&ArenaNode<C>>> {
+        // return path of trie nodes corresponding to ranked vote
+        // returns None if there is no existing matching path in trie
+        let mut current = &self.nodes[ROOT as usize];
+        let mut node_path = vec![current];
+        for vote_value in ranked_vote.iter() {
+            let position = current.search_child(&vote_value).ok()?;
+            current = &self.nodes[current.children[position].1 as usize];
+            node_path.push(current);
+        }
+        Some(node_path)
+    }
+
+    fn search_or_create_child(
+        &mut self, parent: u32, vote_value: GenericVoteValues<C>
+    ) -> u32 {
+        let parent_node = &self.nodes[parent as usize];
+        let position = match parent_node.search_child(&vote_value) {
+            Ok(position) => return parent_node.children[position].1,
+            Err(position) => position
+        };
+
+        let child = u32::try_from(self.nodes.len())
+            .expect("arena trie cannot hold more than u32::MAX nodes");
+        self.nodes.push(ArenaNode::new());
+        self.nodes[parent as usize].children
+            .insert(position, (vote_value, child));
+        child
+    }
+
+    pub fn rank_histogram(&self) -> BTreeMap<C, CandidateRankCounts> {
+        build_rank_histogram(
+            &self.rank_counts, &self.unique_candidates, self.get_num_votes()
+        )
+    }
+
+    pub fn positional_scores<F>(&self, weight: F) -> BTreeMap<C, BigRational>
+    where F: Fn(usize) -> BigRational {
+        positional_scores(&self.rank_counts, &self.unique_candidates, weight)
+    }
+
+    pub fn dowdall_scores(&self) -> BTreeMap<C, BigRational> {
+        self.positional_scores(dowdall_weight)
+    }
+
+    pub fn run_election(
+        &self, votes: Vec<GenericRankedVote<C>>
+    ) -> Option<C> {
+        let mut rcv = GenericArenaVoteTrie::new();
+        rcv.set_elimination_strategy(self.elimination_strategy);
+        rcv.unique_candidates = self.declared_candidates.clone();
+        rcv.declared_candidates = self.declared_candidates.clone();
+        rcv.excluded_candidates = self.excluded_candidates.clone();
+        rcv.insert_votes(votes);
+        rcv.determine_winner()
+    }
+}
+
+impl<C: CandidateId> TabulationSource<C> for GenericArenaVoteTrie<C> {
+    type Key = C;
+    type Trie = Self;
+
+    fn tabulator(&self) -> Tabulator<'_, C, Self> {
+        Tabulator {
+            trie: self,
+            elimination_strategy: self.elimination_strategy,
+            unique_candidates: &self.unique_candidates,
+            excluded_candidates: &self.excluded_candidates,
+            rank_counts: Some(&self.rank_counts)
+        }
+    }
+
+    fn elimination_strategy_mut(&mut self) -> &mut EliminationStrategies {
+        &mut self.elimination_strategy
+    }
+
+    fn excluded_candidates_mut(&mut self) -> &mut HashSet<C> {
+        &mut self.excluded_candidates
+    }
+
+    fn to_key(&self, candidate: &C) -> Option<C> {
+        Some(candidate.clone())
+    }
+
+    fn to_candidate(&self, candidate: &C) -> C {
+        candidate.clone()
+    }
+}
+
+impl<C: CandidateId> Extend<GenericArenaVoteTrie<C>> for GenericArenaVoteTrie<C> {
+    fn extend<T: IntoIterator<Item = GenericArenaVoteTrie<C>>>(
+        &mut self, tries: T
+    ) {
+        for trie in tries {
+            self.merge(trie);
+        }
+    }
+}
+
+impl<C: CandidateId> Sum for GenericArenaVoteTrie<C> {
+    fn sum<I: Iterator<Item = GenericArenaVoteTrie<C>>>(tries: I) -> Self {
+        let mut merged_trie = GenericArenaVoteTrie::new();
+        merged_trie.extend(tries);
+        merged_trie
+    }
+}
+
+impl<C: CandidateId> TrieTraversal<C> for GenericArenaVoteTrie<C> {
+    type Node<'a> = &'a ArenaNode<C> where C: 'a;
+
+    fn root_node(&self) -> &ArenaNode<C> {
+        &self.nodes[ROOT as usize]
+    }
+
+    fn node_votes<'a>(&'a self, node: &'a ArenaNode<C>) -> u64 {
+        node.num_votes
+    }
+
+    fn visit_children<'a, F>(&'a self, node: &'a ArenaNode<C>, mut visit: F)
+    where F: FnMut(&GenericVoteValues<C>, Self::Node<'a>) {
+        for (vote_value, child) in &node.children {
+            visit(vote_value, &self.nodes[*child as usize]);
+        }
+    }
+}
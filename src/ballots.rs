@@ -0,0 +1,96 @@
+use crate::{CandidateId, GenericRankedChoiceVoteTrie};
+use crate::arena::GenericArenaVoteTrie;
+use crate::traversal::TrieTraversal;
+use crate::vote::{GenericRankedVote, GenericVoteValues};
+
+// a node left to visit, with the edge leading into it and the
+// length of its parent's path
+type PendingNode<'a, C, T> = (
+    <T as TrieTraversal<C>>::Node<'a>, usize, Option<GenericVoteValues<C>>
+);
+
+pub struct Ballots<'a, C, T = GenericRankedChoiceVoteTrie<C>>
+where T: TrieTraversal<C> + 'a {
+    /*
+    iterates over every distinct ballot stored in a trie along with
+    the number of times it was cast. Ballots are read off the trie
+    depth-first with children visited in sorted order, so iteration
+    order is deterministic and ballots sharing a prefix come together
+    */
+    trie: &'a T,
+    stack: Vec<PendingNode<'a, C, T>>,
+    path: Vec<GenericVoteValues<C>>
+}
+
+impl<'a, C: CandidateId, T: TrieTraversal<C>> Ballots<'a, C, T> {
+    fn new(trie: &'a T) -> Self {
+        Ballots {
+            trie, stack: vec![(trie.root_node(), 0, None)], path: Vec::new()
+        }
+    }
+
+    fn path_to_vote(&self) -> GenericRankedVote<C> {
+        let mut rankings = Vec::with_capacity(self.path.len());
+        let mut special_vote = None;
+        for vote_value in &self.path {
+            match vote_value {
+                GenericVoteValues::Candidate(candidate) => {
+                    rankings.push(candidate.clone());
+                }
+                GenericVoteValues::SpecialVote(special) => {
+                    special_vote = Some(*special);
+                }
+            }
+        }
+        GenericRankedVote::new(rankings, special_vote)
+            .expect("trie paths are built from valid ballots")
+    }
+}
+
+impl<'a, C: CandidateId, T: TrieTraversal<C>> Iterator for Ballots<'a, C, T> {
+    type Item = (GenericRankedVote<C>, u64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((node, parent_len, vote_value)) = self.stack.pop() {
+            self.path.truncate(parent_len);
+            if let Some(vote_value) = vote_value {
+                self.path.push(vote_value);
+            }
+
+            let mut children = Vec::new();
+            self.trie.visit_children(node, |child_value, child| {
+                children.push((child_value.clone(), child));
+            });
+            children.sort_by(|(value_a, _), (value_b, _)| value_b.cmp(value_a));
+            let mut child_votes = 0;
+            for (child_value, child) in children {
+                child_votes += self.trie.node_votes(child);
+                self.stack.push((child, self.path.len(), Some(child_value)));
+            }
+
+            // ballots that end at this node rather than carrying on
+            let terminating_votes = self.trie.node_votes(node) - child_votes;
+            if terminating_votes > 0 && !self.path.is_empty() {
+                return Some((self.path_to_vote(), terminating_votes));
+            }
+        }
+        None
+    }
+}
+
+impl<C: CandidateId> GenericRankedChoiceVoteTrie<C> {
+    pub fn ballots(&self) -> Ballots<'_, C> {
+        /*
+        returns an iterator over each distinct ballot in the trie
+        together with how many times it was cast, which is enough
+        to rebuild the trie or export the ballots elsewhere
+        */
+        Ballots::new(self)
+    }
+}
+
+impl<C: CandidateId> GenericArenaVoteTrie<C> {
+    pub fn ballots(&self) -> Ballots<'_, C, Self> {
+        Ballots::new(self)
+    }
+}
@@ -0,0 +1,429 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::io::{self, Read, Write};
+use crate::{CandidateId, GenericRankedChoiceVoteTrie, GenericTrieNode};
+use crate::arena::GenericArenaVoteTrie;
+use crate::tabulation::count_rankings;
+use crate::traversal::TrieTraversal;
+use crate::vote::{GenericVoteValues, SpecialVotes};
+
+const MAGIC_BYTES: &[u8; 4] = b"TRCV";
+// version 1 stored f32 dowdall scores instead of rank counts,
+// and is no longer read
+pub const FORMAT_VERSION: u8 = 2;
+
+// child keys in the encoded trie: special votes take the first
+// values, candidates are stored as an offset into the candidate table
+const WITHHOLD_KEY: u64 = 0;
+const ABSTAIN_KEY: u64 = 1;
+const CANDIDATE_KEY_OFFSET: u64 = 2;
+
+#[derive(Debug)]
+pub enum BinaryFormatErrors {
+    Io(io::Error),
+    InvalidMagicBytes,
+    UnsupportedVersion(u8),
+    VarintOverflow,
+    InvalidUtf8,
+    InvalidCandidateIndex(u64),
+    InconsistentVoteCounts,
+    InconsistentRankCounts,
+    DuplicateChildKey(u64),
+    TrieTooDeep,
+    InvalidNodeTable
+}
+
+impl fmt::Display for BinaryFormatErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BinaryFormatErrors::Io(err) => write!(f, "IO error: {}", err),
+            BinaryFormatErrors::InvalidMagicBytes => {
+                write!(f, "Not a serialized ranked choice vote trie")
+            },
+            BinaryFormatErrors::UnsupportedVersion(version) => {
+                write!(f, "Unsupported format version {}", version)
+            },
+            BinaryFormatErrors::VarintOverflow => write!(f, "Varint overflow"),
+            BinaryFormatErrors::InvalidUtf8 => write!(f, "Invalid UTF-8 string"),
+            BinaryFormatErrors::InvalidCandidateIndex(index) => {
+                write!(f, "Invalid candidate index {}", index)
+            },
+            BinaryFormatErrors::InconsistentVoteCounts => {
+                write!(f, "Child node has no votes or more votes than its parent")
+            },
+            BinaryFormatErrors::InconsistentRankCounts => {
+                write!(f, "Rank counts don't match the ballots in the trie")
+            },
+            BinaryFormatErrors::DuplicateChildKey(key) => {
+                write!(f, "Node has more than one child with key {}", key)
+            },
+            BinaryFormatErrors::TrieTooDeep => {
+                write!(f, "Trie is deeper than any valid ballot")
+            },
+            BinaryFormatErrors::InvalidNodeTable => {
+                write!(f, "Node table is truncated or malformed")
+            },
+        }
+    }
+}
+
+impl From<io::Error> for BinaryFormatErrors {
+    fn from(err: io::Error) -> Self {
+        BinaryFormatErrors::Io(err)
+    }
+}
+
+pub fn write_varint<W: Write>(writer: &mut W, value: u64) -> io::Result<()> {
+    // LEB128: 7 bits of the value per byte, high bit set if more follow
+    let mut remaining = value;
+    loop {
+        let byte = (remaining & 0x7f) as u8;
+        remaining >>= 7;
+        if remaining == 0 {
+            return writer.write_all(&[byte]);
+        }
+        writer.write_all(&[byte | 0x80])?;
+    }
+}
+
+pub fn read_varint<R: Read>(reader: &mut R) -> Result<u64, BinaryFormatErrors> {
+    let mut value: u64 = 0;
+    let mut shift: u32 = 0;
+    loop {
+        let mut byte = [0u8; 1];
+        reader.read_exact(&mut byte)?;
+        let bits = u64::from(byte[0] & 0x7f);
+        if shift >= 64 || (shift == 63 && bits > 1) {
+            return Err(BinaryFormatErrors::VarintOverflow);
+        }
+
+        value |= bits << shift;
+        if byte[0] & 0x80 == 0 { return Ok(value) }
+        shift += 7;
+    }
+}
+
+// candidate identifiers that can be written to the binary trie format
+pub trait BinaryCandidate: Sized {
+    fn write_binary<W: Write>(&self, writer: &mut W) -> io::Result<()>;
+    fn read_binary<R: Read>(reader: &mut R) -> Result<Self, BinaryFormatErrors>;
+}
+
+macro_rules! impl_unsigned_binary_candidate {
+    ($($int_type:ty),*) => {$(
+        impl BinaryCandidate for $int_type {
+            fn write_binary<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+                write_varint(writer, u64::from(*self))
+            }
+
+            fn read_binary<R: Read>(
+                reader: &mut R
+            ) -> Result<Self, BinaryFormatErrors> {
+                <$int_type>::try_from(read_varint(reader)?)
+                    .map_err(|_| BinaryFormatErrors::VarintOverflow)
+            }
+        }
+    )*};
+}
+
+impl_unsigned_binary_candidate!(u8, u16, u32, u64);
+
+impl BinaryCandidate for String {
+    fn write_binary<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        write_varint(writer, self.len() as u64)?;
+        writer.write_all(self.as_bytes())
+    }
+
+    fn read_binary<R: Read>(reader: &mut R) -> Result<Self, BinaryFormatErrors> {
+        let length = read_varint(reader)?;
+        let mut bytes = Vec::new();
+        reader.take(length).read_to_end(&mut bytes)?;
+        if bytes.len() as u64 != length {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+        String::from_utf8(bytes).map_err(|_| BinaryFormatErrors::InvalidUtf8)
+    }
+}
+
+fn write_node<'a, C: CandidateId, T: TrieTraversal<C>, W: Write>(
+    writer: &mut W, trie: &'a T, node: T::Node<'a>,
+    candidate_indexes: &HashMap<&C, u64>
+) -> io::Result<()> {
+    let mut children = Vec::new();
+    trie.visit_children(node, |vote_value, child| {
+        children.push((vote_value.clone(), child));
+    });
+    write_varint(writer, trie.node_votes(node))?;
+    write_varint(writer, children.len() as u64)?;
+
+    // children are written in sorted order so that identical tries
+    // always serialize to identical bytes
+    children.sort_by(|(value_a, _), (value_b, _)| value_a.cmp(value_b));
+
+    for (vote_value, child) in children {
+        let key = match &vote_value {
+            GenericVoteValues::SpecialVote(SpecialVotes::WITHHOLD) => {
+                WITHHOLD_KEY
+            },
+            GenericVoteValues::SpecialVote(SpecialVotes::ABSTAIN) => {
+                ABSTAIN_KEY
+            },
+            GenericVoteValues::Candidate(candidate) => {
+                CANDIDATE_KEY_OFFSET + candidate_indexes[candidate]
+            }
+        };
+        write_varint(writer, key)?;
+        write_node(writer, trie, child, candidate_indexes)?;
+    }
+    Ok(())
+}
+
+fn read_node<C: CandidateId, R: Read>(
+    reader: &mut R, candidates: &[C], max_depth: usize
+) -> Result<GenericTrieNode<C>, BinaryFormatErrors> {
+    /*
+    reads a node and (recursively) its children. Ballots rank each
+    candidate at most once followed by at most one special vote, so
+    nodes can't be nested deeper than max_depth in a valid trie
+    */
+    let Some(max_depth) = max_depth.checked_sub(1) else {
+        return Err(BinaryFormatErrors::TrieTooDeep);
+    };
+    let mut node = GenericTrieNode::new();
+    node.num_votes = read_varint(reader)?;
+    let num_children = read_varint(reader)?;
+    let mut child_votes: u64 = 0;
+
+    for _ in 0..num_children {
+        let key = read_varint(reader)?;
+        let vote_value = match key {
+            WITHHOLD_KEY => GenericVoteValues::SpecialVote(SpecialVotes::WITHHOLD),
+            ABSTAIN_KEY => GenericVoteValues::SpecialVote(SpecialVotes::ABSTAIN),
+            _ => {
+                let index = key - CANDIDATE_KEY_OFFSET;
+                let candidate = usize::try_from(index).ok()
+                    .and_then(|index| candidates.get(index))
+                    .ok_or(BinaryFormatErrors::InvalidCandidateIndex(index))?;
+                GenericVoteValues::Candidate(candidate.clone())
+            }
+        };
+
+        let child = read_node(reader, candidates, max_depth)?;
+        // every ballot passing through a node counts towards its votes
+        if child.num_votes == 0 {
+            return Err(BinaryFormatErrors::InconsistentVoteCounts);
+        }
+        child_votes = child_votes.checked_add(child.num_votes)
+            .ok_or(BinaryFormatErrors::InconsistentVoteCounts)?;
+        if node.children.insert(vote_value, child).is_some() {
+            return Err(BinaryFormatErrors::DuplicateChildKey(key));
+        }
+    }
+
+    if child_votes > node.num_votes {
+        return Err(BinaryFormatErrors::InconsistentVoteCounts);
+    }
+    Ok(node)
+}
+
+pub(crate) fn write_candidates<'a, C, W, I>(
+    writer: &mut W, candidates: I
+) -> io::Result<()>
+where
+    C: BinaryCandidate + 'a, W: Write, I: ExactSizeIterator<Item = &'a C>
+{
+    write_varint(writer, candidates.len() as u64)?;
+    for candidate in candidates {
+        candidate.write_binary(writer)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn read_candidates<C: BinaryCandidate, R: Read>(
+    reader: &mut R
+) -> Result<Vec<C>, BinaryFormatErrors> {
+    let num_candidates = read_varint(reader)?;
+    let mut candidates = Vec::new();
+    for _ in 0..num_candidates {
+        candidates.push(C::read_binary(reader)?);
+    }
+    Ok(candidates)
+}
+
+fn write_trie<C, T, W>(
+    writer: &mut W, trie: &T, unique_candidates: &HashSet<C>,
+    declared_candidates: &HashSet<C>, rank_counts: &HashMap<C, Vec<u64>>
+) -> io::Result<()>
+where C: CandidateId + BinaryCandidate, T: TrieTraversal<C>, W: Write {
+    writer.write_all(MAGIC_BYTES)?;
+    writer.write_all(&[FORMAT_VERSION])?;
+
+    let mut candidates: Vec<&C> = unique_candidates.iter().collect();
+    candidates.sort();
+    let candidate_indexes: HashMap<&C, u64> = candidates.iter()
+        .enumerate().map(|(index, &candidate)| (candidate, index as u64))
+        .collect();
+    write_candidates(writer, candidates.iter().copied())?;
+
+    let mut declared_candidates: Vec<&C> = declared_candidates.iter().collect();
+    declared_candidates.sort();
+    write_varint(writer, declared_candidates.len() as u64)?;
+    for candidate in declared_candidates {
+        write_varint(writer, candidate_indexes[candidate])?;
+    }
+
+    let mut rank_counts: Vec<(&C, &Vec<u64>)> = rank_counts.iter().collect();
+    rank_counts.sort_by_key(|(candidate, _)| *candidate);
+    write_varint(writer, rank_counts.len() as u64)?;
+    for (candidate, candidate_rank_counts) in rank_counts {
+        write_varint(writer, candidate_indexes[candidate])?;
+        write_varint(writer, candidate_rank_counts.len() as u64)?;
+        for num_votes in candidate_rank_counts {
+            write_varint(writer, *num_votes)?;
+        }
+    }
+
+    write_node(writer, trie, trie.root_node(), &candidate_indexes)
+}
+
+impl<C: CandidateId + BinaryCandidate> GenericRankedChoiceVoteTrie<C> {
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        /*
+        writes the trie's vote counts, candidates and rank counts in
+        a compact, versioned binary format that can be loaded back with
+        read_from. The elimination strategy and excluded candidates
+        are tabulation settings and are not written
+        */
+        write_trie(
+            writer, self, &self.unique_candidates, &self.declared_candidates,
+            &self.rank_counts
+        )
+    }
+
+    pub fn read_from<R: Read>(
+        reader: &mut R
+    ) -> Result<GenericRankedChoiceVoteTrie<C>, BinaryFormatErrors> {
+        let mut magic_bytes = [0u8; 4];
+        reader.read_exact(&mut magic_bytes)?;
+        if &magic_bytes != MAGIC_BYTES {
+            return Err(BinaryFormatErrors::InvalidMagicBytes);
+        }
+
+        let mut version = [0u8; 1];
+        reader.read_exact(&mut version)?;
+        let version = version[0];
+        if version != FORMAT_VERSION {
+            return Err(BinaryFormatErrors::UnsupportedVersion(version));
+        }
+
+        let candidates: Vec<C> = read_candidates(reader)?;
+        let get_candidate = |index: u64| -> Result<C, BinaryFormatErrors> {
+            usize::try_from(index).ok()
+                .and_then(|index| candidates.get(index)).cloned()
+                .ok_or(BinaryFormatErrors::InvalidCandidateIndex(index))
+        };
+
+        let mut declared_candidates: HashSet<C> = HashSet::new();
+        let num_declared_candidates = read_varint(reader)?;
+        for _ in 0..num_declared_candidates {
+            declared_candidates.insert(get_candidate(read_varint(reader)?)?);
+        }
+
+        let mut rank_counts: HashMap<C, Vec<u64>> = HashMap::new();
+        let num_candidate_scores = read_varint(reader)?;
+        for _ in 0..num_candidate_scores {
+            let candidate = get_candidate(read_varint(reader)?)?;
+            let num_rankings = read_varint(reader)?;
+            let mut candidate_rank_counts = Vec::new();
+            for _ in 0..num_rankings {
+                candidate_rank_counts.push(read_varint(reader)?);
+            }
+            rank_counts.insert(candidate, candidate_rank_counts);
+        }
+
+        // the root, one node per ranked candidate and a special vote
+        let max_depth = candidates.len() + 2;
+        let root = read_node(reader, &candidates, max_depth)?;
+        let mut rcv = GenericRankedChoiceVoteTrie::new();
+        rcv.root = root;
+        rcv.unique_candidates = candidates.into_iter().collect();
+        rcv.declared_candidates = declared_candidates;
+        rcv.rank_counts = count_rankings(&rcv);
+        if rcv.rank_counts != rank_counts {
+            return Err(BinaryFormatErrors::InconsistentRankCounts);
+        }
+        Ok(rcv)
+    }
+}
+
+impl<C: CandidateId + BinaryCandidate> GenericArenaVoteTrie<C> {
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        // same format as GenericRankedChoiceVoteTrie::write_to, so
+        // files can be loaded back into either kind of trie
+        write_trie(
+            writer, self, &self.unique_candidates, &self.declared_candidates,
+            &self.rank_counts
+        )
+    }
+
+    pub fn read_from<R: Read>(
+        reader: &mut R
+    ) -> Result<GenericArenaVoteTrie<C>, BinaryFormatErrors> {
+        let rcv = GenericRankedChoiceVoteTrie::read_from(reader)?;
+        let mut arena_rcv = GenericArenaVoteTrie::new();
+        arena_rcv.add_nodes(&rcv);
+        arena_rcv.rank_counts = rcv.rank_counts;
+        arena_rcv.unique_candidates = rcv.unique_candidates;
+        arena_rcv.declared_candidates = rcv.declared_candidates;
+        Ok(arena_rcv)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_varint_round_trip() {
+        for value in [0, 1, 127, 128, 300, u64::from(u32::MAX), u64::MAX] {
+            let mut bytes = Vec::new();
+            write_varint(&mut bytes, value).unwrap();
+            assert_eq!(read_varint(&mut bytes.as_slice()).unwrap(), value);
+        }
+    }
+
+    fn read_crafted(rank_counts: &[u8], nodes: &[u8]) -> BinaryFormatErrors {
+        // a single candidate (5), with the given rank counts and nodes
+        let mut bytes = b"TRCV\x02\x01\x05\x00".to_vec();
+        bytes.extend_from_slice(rank_counts);
+        bytes.extend_from_slice(nodes);
+        crate::RankedChoiceVoteTrie::read_from(&mut bytes.as_slice())
+            .unwrap_err()
+    }
+
+    #[test]
+    fn test_read_rejects_inconsistent_tries() {
+        let error = read_crafted(&[0], &[1, 1, 2, 0, 0]);
+        assert!(matches!(error, BinaryFormatErrors::InconsistentVoteCounts));
+        let error = read_crafted(&[1, 0, 1, 2], &[2, 2, 2, 1, 0, 2, 1, 0]);
+        assert!(matches!(error, BinaryFormatErrors::DuplicateChildKey(2)));
+        let error = read_crafted(&[1, 0, 1, 3], &[1, 1, 2, 1, 0]);
+        assert!(matches!(error, BinaryFormatErrors::InconsistentRankCounts));
+
+        // a ballot can't rank the only candidate three times over
+        let mut nested_nodes = [1, 1, 2].repeat(3);
+        nested_nodes.extend_from_slice(&[1, 0]);
+        let error = read_crafted(&[1, 0, 1, 1], &nested_nodes);
+        assert!(matches!(error, BinaryFormatErrors::TrieTooDeep));
+    }
+
+    #[test]
+    fn test_invalid_magic_bytes() {
+        let read_result = crate::RankedChoiceVoteTrie::read_from(
+            &mut b"NOPE\x01".as_slice()
+        );
+        assert!(matches!(
+            read_result, Err(BinaryFormatErrors::InvalidMagicBytes)
+        ));
+    }
+}
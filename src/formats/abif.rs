@@ -0,0 +1,268 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use crate::GenericRankedChoiceVoteTrie;
+use crate::formats::{untied_vote, LineReader};
+use crate::vote::{SpecialVotes, VoteErrors};
+
+/*
+ABIF (Aggregated Ballot Information Format) files list candidate
+declarations and aggregated ballots, one per line:
+
+# comments start with a hash
+=A:[Alice Smith]
+=B:[Bob]
+27:A>B>C
+12:B=C>A
+3:[Dave Jones]>A
+
+i.e. the number of ballots, then the candidates from most to least
+preferred, with ">" between ranks and "=" between tied candidates.
+Candidate tokens containing other characters are written in brackets.
+Lines starting with "{" hold JSON metadata and are kept as is
+*/
+
+#[derive(Debug)]
+pub enum AbifErrors {
+    Io(io::Error),
+    InvalidNumber { line: usize, token: String },
+    ZeroCount { line: usize },
+    InvalidLine { line: usize },
+    InvalidCandidate { line: usize },
+    // rated ballots (e.g. A/5>B/3) have no ranked vote equivalent
+    UnsupportedScores { line: usize },
+    InvalidVote { line: usize, error: VoteErrors },
+    // ABIF ballots can't hold withheld votes, or abstentions after rankings
+    UnsupportedSpecialVote(SpecialVotes)
+}
+
+impl AbifErrors {
+    pub fn line(&self) -> Option<usize> {
+        // the (1-based) line number the error was found on, if any
+        match self {
+            AbifErrors::InvalidNumber { line, .. } |
+            AbifErrors::ZeroCount { line } |
+            AbifErrors::InvalidLine { line } |
+            AbifErrors::InvalidCandidate { line } |
+            AbifErrors::UnsupportedScores { line } |
+            AbifErrors::InvalidVote { line, .. } => Some(*line),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Display for AbifErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AbifErrors::Io(err) => write!(f, "IO error: {}", err),
+            AbifErrors::InvalidNumber { line, token } => {
+                write!(f, "line {}: invalid number {:?}", line, token)
+            },
+            AbifErrors::ZeroCount { line } => {
+                write!(f, "line {}: ballot count must be at least 1", line)
+            },
+            AbifErrors::InvalidLine { line } => {
+                write!(f, "line {}: expected \"count:ballot\"", line)
+            },
+            AbifErrors::InvalidCandidate { line } => {
+                write!(f, "line {}: invalid candidate token", line)
+            },
+            AbifErrors::UnsupportedScores { line } => {
+                write!(f, "line {}: rated ballots are not supported", line)
+            },
+            AbifErrors::InvalidVote { line, error } => {
+                write!(f, "line {}: {}", line, error)
+            },
+            AbifErrors::UnsupportedSpecialVote(special_vote) => {
+                write!(f, "{:?} votes can't be written to ABIF", special_vote)
+            },
+        }
+    }
+}
+
+impl From<io::Error> for AbifErrors {
+    fn from(err: io::Error) -> Self {
+        AbifErrors::Io(err)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AbifElection {
+    // display names of declared candidates, by candidate token
+    pub candidate_names: BTreeMap<String, String>,
+    // raw JSON metadata lines
+    pub metadata: Vec<String>,
+    pub trie: GenericRankedChoiceVoteTrie<String>,
+    // number of ballots whose rankings were cut short at a tie
+    pub num_truncated_votes: u64
+}
+
+fn is_bare_token_char(character: char) -> bool {
+    character.is_alphanumeric() || character == '_' || character == '-'
+}
+
+fn strip_comment(text: &str) -> &str {
+    // hashes inside bracketed candidate names don't start comments
+    let mut in_brackets = false;
+    for (index, character) in text.char_indices() {
+        match character {
+            '[' => in_brackets = true,
+            ']' => in_brackets = false,
+            '#' if !in_brackets => return &text[..index],
+            _ => {}
+        }
+    }
+    text
+}
+
+fn parse_token(line: usize, text: &str) -> Result<(String, usize), AbifErrors> {
+    // reads a bare or bracketed candidate token from the start of text,
+    // returning the token and the number of bytes it took up
+    if let Some(bracketed) = text.strip_prefix('[') {
+        let end = bracketed.find(']')
+            .ok_or(AbifErrors::InvalidCandidate { line })?;
+        return Ok((bracketed[..end].to_string(), end + 2));
+    }
+    let end = text.find(|character| !is_bare_token_char(character))
+        .unwrap_or(text.len());
+    if end == 0 {
+        return Err(AbifErrors::InvalidCandidate { line });
+    }
+    Ok((text[..end].to_string(), end))
+}
+
+fn parse_rankings(
+    line: usize, text: &str
+) -> Result<Vec<Vec<String>>, AbifErrors> {
+    // splits "A>B=C>D" into groups of equally ranked candidates
+    let mut groups: Vec<Vec<String>> = Vec::new();
+    let mut rest = text.trim_start();
+    if rest.is_empty() {
+        return Ok(groups);
+    }
+    let mut group = Vec::new();
+    loop {
+        let (token, length) = parse_token(line, rest)?;
+        group.push(token);
+        rest = rest[length..].trim_start();
+
+        match rest.chars().next() {
+            None => break,
+            Some('>') => groups.push(std::mem::take(&mut group)),
+            Some('=') => {}
+            Some('/') => return Err(AbifErrors::UnsupportedScores { line }),
+            Some(_) => return Err(AbifErrors::InvalidLine { line })
+        }
+        rest = rest[1..].trim_start();
+    }
+    groups.push(group);
+    Ok(groups)
+}
+
+fn parse_declaration(
+    line: usize, text: &str
+) -> Result<(String, String), AbifErrors> {
+    // "=A:[Alice Smith]" declares candidate A named Alice Smith
+    let (token, length) = parse_token(line, text.trim_start())?;
+    let rest = text.trim_start()[length..].trim();
+    let name = match rest.strip_prefix(':') {
+        None if rest.is_empty() => token.clone(),
+        None => return Err(AbifErrors::InvalidLine { line }),
+        Some(name) => {
+            let name = name.trim();
+            name.strip_prefix('[').and_then(|name| name.strip_suffix(']'))
+                .unwrap_or(name).to_string()
+        }
+    };
+    Ok((token, name))
+}
+
+pub fn read_abif<R: BufRead>(reader: R) -> Result<AbifElection, AbifErrors> {
+    /*
+    loads ABIF ballots into a trie keyed by candidate token, with
+    declared candidates declared in the trie. Ranked votes can't hold
+    ties, so ballots are cut short at their first tie (see untied_vote)
+    */
+    let mut lines = LineReader::new(reader);
+    let mut trie = GenericRankedChoiceVoteTrie::new();
+    let mut candidate_names = BTreeMap::new();
+    let mut metadata = Vec::new();
+    let mut num_truncated_votes = 0;
+
+    while let Some((line, text)) = lines.next_line()? {
+        if text.starts_with('{') {
+            metadata.push(text);
+            continue;
+        }
+        let text = strip_comment(&text).trim();
+        if text.is_empty() { continue }
+
+        if let Some(declaration) = text.strip_prefix('=') {
+            let (token, name) = parse_declaration(line, declaration)?;
+            trie.declare_candidates(std::slice::from_ref(&token));
+            candidate_names.insert(token, name);
+            continue;
+        }
+
+        let (count, rankings) = text.split_once(':')
+            .ok_or(AbifErrors::InvalidLine { line })?;
+        let count: u64 = count.trim().parse().map_err(|_| {
+            AbifErrors::InvalidNumber { line, token: count.trim().to_string() }
+        })?;
+        if count == 0 {
+            return Err(AbifErrors::ZeroCount { line });
+        }
+        let groups = parse_rankings(line, rankings)?;
+        let (vote, is_truncated) = untied_vote(&groups)
+            .map_err(|error| AbifErrors::InvalidVote { line, error })?;
+        if is_truncated { num_truncated_votes += count; }
+        trie.insert_weighted_vote(vote, count);
+    }
+
+    Ok(AbifElection { candidate_names, metadata, trie, num_truncated_votes })
+}
+
+fn format_token(candidate: &str) -> String {
+    let is_bare = !candidate.is_empty() &&
+        candidate.chars().all(is_bare_token_char);
+    match is_bare {
+        true => candidate.to_string(),
+        false => format!("[{}]", candidate.replace(']', ")"))
+    }
+}
+
+pub fn write_abif<W: Write>(
+    writer: &mut W, rcv: &GenericRankedChoiceVoteTrie<String>,
+    candidate_names: &BTreeMap<String, String>
+) -> Result<(), AbifErrors> {
+    /*
+    writes a declaration for every candidate (using candidate_names
+    where given) followed by the trie's distinct ballots and their
+    counts, so ballots stay aggregated. Ballots that only abstain are
+    written without rankings
+    */
+    let candidates: BTreeSet<&String> = rcv.unique_candidates.iter()
+        .chain(candidate_names.keys())
+        .collect();
+    for candidate in candidates {
+        let name = candidate_names.get(candidate).unwrap_or(candidate);
+        writeln!(
+            writer, "={}:[{}]", format_token(candidate), name.replace(']', ")")
+        )?;
+    }
+
+    for (vote, count) in rcv.ballots() {
+        if let Some(special_vote) = vote.special_vote() {
+            let is_blank_ballot =
+                special_vote == SpecialVotes::ABSTAIN && vote.len() == 1;
+            if !is_blank_ballot {
+                return Err(AbifErrors::UnsupportedSpecialVote(special_vote));
+            }
+        }
+        let rankings: Vec<String> = vote.rankings().iter()
+            .map(|candidate| format_token(candidate))
+            .collect();
+        writeln!(writer, "{}:{}", count, rankings.join(">"))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,336 @@
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use crate::{RankedChoiceVoteTrie, TrieTabulation};
+use crate::formats::LineReader;
+use crate::vote::{RankedVote, SpecialVotes, VoteErrors};
+
+/*
+BLT ballot files (as used by OpenSTV, OpaVote and the ERS) look like:
+
+4 2
+-2
+3 1 2 4 0
+1 3 0
+0
+"Alice"
+"Bob"
+"Carol"
+"Dave"
+"Election title"
+
+i.e. the number of candidates and seats, an optional line of
+withdrawn candidates (as negative ids), one line per ballot
+(weight, candidate ids in ranked order, 0), a lone 0, then the
+candidate names in id order and the election title.
+Candidate ids are 1-based indexes into the candidate names
+*/
+
+#[derive(Debug)]
+pub enum BltErrors {
+    Io(io::Error),
+    UnexpectedEndOfFile,
+    InvalidHeader { line: usize },
+    InvalidNumber { line: usize, token: String },
+    InvalidCandidate { line: usize, candidate: i64 },
+    TiedRankings { line: usize },
+    ZeroWeight { line: usize },
+    MissingBallotTerminator { line: usize },
+    InvalidVote { line: usize, error: VoteErrors },
+    InvalidString { line: usize },
+    // BLT ballots can't hold withheld votes, or abstentions after rankings
+    UnsupportedSpecialVote(SpecialVotes),
+    // exported candidates must be numbered from 1 to the number of names
+    UnnamedCandidate(u32)
+}
+
+impl BltErrors {
+    pub fn line(&self) -> Option<usize> {
+        // the (1-based) line number the error was found on, if any
+        match self {
+            BltErrors::InvalidHeader { line } |
+            BltErrors::InvalidNumber { line, .. } |
+            BltErrors::InvalidCandidate { line, .. } |
+            BltErrors::TiedRankings { line } |
+            BltErrors::ZeroWeight { line } |
+            BltErrors::MissingBallotTerminator { line } |
+            BltErrors::InvalidVote { line, .. } |
+            BltErrors::InvalidString { line } => Some(*line),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Display for BltErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BltErrors::Io(err) => write!(f, "IO error: {}", err),
+            BltErrors::UnexpectedEndOfFile => {
+                write!(f, "Unexpected end of BLT file")
+            },
+            BltErrors::InvalidHeader { line } => {
+                write!(f, "line {}: expected candidate and seat counts", line)
+            },
+            BltErrors::InvalidNumber { line, token } => {
+                write!(f, "line {}: invalid number {:?}", line, token)
+            },
+            BltErrors::InvalidCandidate { line, candidate } => {
+                write!(f, "line {}: invalid candidate {}", line, candidate)
+            },
+            BltErrors::TiedRankings { line } => {
+                write!(f, "line {}: tied rankings are not supported", line)
+            },
+            BltErrors::ZeroWeight { line } => {
+                write!(f, "line {}: ballot weight must be at least 1", line)
+            },
+            BltErrors::MissingBallotTerminator { line } => {
+                write!(f, "line {}: ballot does not end with 0", line)
+            },
+            BltErrors::InvalidVote { line, error } => {
+                write!(f, "line {}: {}", line, error)
+            },
+            BltErrors::InvalidString { line } => {
+                write!(f, "line {}: expected a quoted string", line)
+            },
+            BltErrors::UnsupportedSpecialVote(special_vote) => {
+                write!(f, "{:?} votes can't be written to BLT", special_vote)
+            },
+            BltErrors::UnnamedCandidate(candidate) => {
+                write!(f, "Candidate {} has no name", candidate)
+            },
+        }
+    }
+}
+
+impl From<io::Error> for BltErrors {
+    fn from(err: io::Error) -> Self {
+        BltErrors::Io(err)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BltElection {
+    pub title: String,
+    pub num_seats: u32,
+    // candidate n is named candidate_names[n - 1]
+    pub candidate_names: Vec<String>,
+    pub withdrawn_candidates: Vec<u32>,
+    // distinct ballots and their weights
+    pub ballots: Vec<(RankedVote, u64)>
+}
+
+impl BltElection {
+    pub fn from_trie(
+        rcv: &RankedChoiceVoteTrie, title: &str, num_seats: u32,
+        candidate_names: Vec<String>
+    ) -> Result<BltElection, BltErrors> {
+        /*
+        exports the ballots in the trie as weighted BLT ballot lines.
+        Candidate ids must be between 1 and the number of candidate
+        names. Ballots that only abstain are written as blank ballots
+        */
+        let num_candidates = candidate_names.len() as u32;
+        let mut ballots = Vec::new();
+        for (vote, count) in rcv.ballots() {
+            if let Some(special_vote) = vote.special_vote() {
+                let is_blank_ballot =
+                    special_vote == SpecialVotes::ABSTAIN && vote.len() == 1;
+                if !is_blank_ballot {
+                    return Err(BltErrors::UnsupportedSpecialVote(special_vote));
+                }
+            }
+            for &candidate in vote.rankings() {
+                if candidate == 0 || candidate > num_candidates {
+                    return Err(BltErrors::UnnamedCandidate(candidate));
+                }
+            }
+            ballots.push((vote, count));
+        }
+
+        let mut withdrawn_candidates: Vec<u32> =
+            rcv.excluded_candidates.iter().copied().collect();
+        withdrawn_candidates.sort();
+        Ok(BltElection {
+            title: title.to_string(), num_seats, candidate_names,
+            withdrawn_candidates, ballots
+        })
+    }
+
+    pub fn from_votes(
+        votes: Vec<RankedVote>, title: &str, num_seats: u32,
+        candidate_names: Vec<String>
+    ) -> Result<BltElection, BltErrors> {
+        // identical ballots are aggregated into weighted ballot lines
+        let mut rcv = RankedChoiceVoteTrie::new();
+        rcv.insert_votes(votes);
+        Self::from_trie(&rcv, title, num_seats, candidate_names)
+    }
+
+    pub fn to_votes(&self) -> Vec<RankedVote> {
+        // expands weighted ballots into one vote per ballot
+        let mut votes = Vec::new();
+        for (vote, weight) in &self.ballots {
+            for _ in 0..*weight {
+                votes.push(vote.clone());
+            }
+        }
+        votes
+    }
+
+    pub fn to_trie(&self) -> RankedChoiceVoteTrie {
+        /*
+        builds a trie with every named candidate declared and
+        withdrawn candidates excluded from tabulation
+        */
+        let mut rcv = RankedChoiceVoteTrie::new();
+        let candidates: Vec<u32> =
+            (1..=self.candidate_names.len() as u32).collect();
+        rcv.declare_candidates(&candidates);
+        rcv.exclude_candidates(&self.withdrawn_candidates);
+        for (vote, weight) in &self.ballots {
+            rcv.insert_weighted_vote(vote.clone(), *weight);
+        }
+        rcv
+    }
+}
+
+fn expect_line<R: BufRead>(
+    lines: &mut LineReader<R>
+) -> Result<(usize, String), BltErrors> {
+    lines.next_line()?.ok_or(BltErrors::UnexpectedEndOfFile)
+}
+
+fn parse_number<T: std::str::FromStr>(
+    line: usize, token: &str
+) -> Result<T, BltErrors> {
+    token.parse().map_err(|_| BltErrors::InvalidNumber {
+        line, token: token.to_string()
+    })
+}
+
+fn parse_string(line: usize, text: &str) -> Result<String, BltErrors> {
+    let unquoted = text.strip_prefix('"')
+        .and_then(|text| text.strip_suffix('"'))
+        .ok_or(BltErrors::InvalidString { line })?;
+    Ok(unquoted.to_string())
+}
+
+fn parse_ballot(
+    line: usize, tokens: &[&str], num_candidates: u32
+) -> Result<(RankedVote, u64), BltErrors> {
+    // optional ballot id in brackets, weight, rankings, then 0
+    let tokens = match tokens.first() {
+        Some(token) if token.starts_with('(') => &tokens[1..],
+        _ => tokens
+    };
+    let (weight, rest) = tokens.split_first()
+        .ok_or(BltErrors::MissingBallotTerminator { line })?;
+    let weight: u64 = parse_number(line, weight)?;
+    if weight == 0 {
+        return Err(BltErrors::ZeroWeight { line });
+    }
+    let (terminator, rankings) = rest.split_last()
+        .ok_or(BltErrors::MissingBallotTerminator { line })?;
+    if *terminator != "0" {
+        return Err(BltErrors::MissingBallotTerminator { line });
+    }
+
+    let mut candidates = Vec::new();
+    for token in rankings {
+        // a dash marks a skipped ranking
+        if *token == "-" { continue }
+        if token.contains('=') {
+            return Err(BltErrors::TiedRankings { line });
+        }
+        let candidate: i64 = parse_number(line, token)?;
+        if candidate < 1 || candidate > num_candidates as i64 {
+            return Err(BltErrors::InvalidCandidate { line, candidate });
+        }
+        candidates.push(candidate as u32);
+    }
+
+    // blank ballots count towards nobody
+    let special_vote = match candidates.is_empty() {
+        true => Some(SpecialVotes::ABSTAIN),
+        false => None
+    };
+    let vote = RankedVote::new(candidates, special_vote)
+        .map_err(|error| BltErrors::InvalidVote { line, error })?;
+    Ok((vote, weight))
+}
+
+pub fn read_blt<R: BufRead>(reader: R) -> Result<BltElection, BltErrors> {
+    let mut lines = LineReader::new(reader);
+
+    let (line, header) = expect_line(&mut lines)?;
+    let header: Vec<&str> = header.split_whitespace().collect();
+    let [num_candidates, num_seats] = header[..] else {
+        return Err(BltErrors::InvalidHeader { line });
+    };
+    let num_candidates: u32 = parse_number(line, num_candidates)?;
+    let num_seats: u32 = parse_number(line, num_seats)?;
+
+    let mut withdrawn_candidates = Vec::new();
+    let mut ballots = Vec::new();
+    let (mut line, mut text) = expect_line(&mut lines)?;
+    if text.starts_with('-') {
+        for token in text.split_whitespace() {
+            let candidate: i64 = parse_number(line, token)?;
+            if candidate > -1 || -candidate > num_candidates as i64 {
+                return Err(BltErrors::InvalidCandidate { line, candidate });
+            }
+            withdrawn_candidates.push((-candidate) as u32);
+        }
+        (line, text) = expect_line(&mut lines)?;
+    }
+
+    while text != "0" {
+        let tokens: Vec<&str> = text.split_whitespace().collect();
+        ballots.push(parse_ballot(line, &tokens, num_candidates)?);
+        (line, text) = expect_line(&mut lines)?;
+    }
+
+    let mut candidate_names = Vec::new();
+    for _ in 0..num_candidates {
+        let (line, text) = expect_line(&mut lines)?;
+        candidate_names.push(parse_string(line, &text)?);
+    }
+    let (line, text) = expect_line(&mut lines)?;
+    let title = parse_string(line, &text)?;
+
+    Ok(BltElection {
+        title, num_seats, candidate_names, withdrawn_candidates, ballots
+    })
+}
+
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('"', "'"))
+}
+
+pub fn write_blt<W: Write>(
+    writer: &mut W, election: &BltElection
+) -> io::Result<()> {
+    writeln!(
+        writer, "{} {}", election.candidate_names.len(), election.num_seats
+    )?;
+    if !election.withdrawn_candidates.is_empty() {
+        let withdrawn: Vec<String> = election.withdrawn_candidates.iter()
+            .map(|candidate| format!("-{}", candidate))
+            .collect();
+        writeln!(writer, "{}", withdrawn.join(" "))?;
+    }
+
+    for (vote, weight) in &election.ballots {
+        // special votes have no BLT equivalent and are left out
+        write!(writer, "{}", weight)?;
+        for candidate in vote.rankings() {
+            write!(writer, " {}", candidate)?;
+        }
+        writeln!(writer, " 0")?;
+    }
+    writeln!(writer, "0")?;
+
+    for name in &election.candidate_names {
+        writeln!(writer, "{}", quote(name))?;
+    }
+    writeln!(writer, "{}", quote(&election.title))
+}
@@ -0,0 +1,232 @@
+use std::fmt;
+use std::io::Read;
+use crate::RankedChoiceVoteTrie;
+use crate::rctab::{BallotRules, ExhaustedBallot, ExhaustionReasons, RankMark};
+use crate::registry::CandidateRegistry;
+use crate::vote::RankedVote;
+
+/*
+reads cast vote records exported as CSV, with one row per ballot and
+one column per rank holding the name of the candidate ranked there:
+
+Ballot ID,Precinct,Choice 1,Choice 2,Choice 3
+1,North,Alice,Bob,
+2,North,Bob,overvote,Alice
+
+Candidate names are turned into candidate ids through a registry.
+Bad rows are logged and skipped rather than aborting the import
+*/
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RankColumns {
+    // columns with exactly these headers, in rank order
+    Named(Vec<String>),
+    // columns whose header starts with the prefix, in file order
+    Prefix(String),
+    // columns at these (0-based) positions, in rank order
+    Indexes(Vec<usize>)
+}
+
+#[derive(Clone, Debug)]
+pub struct CsvOptions {
+    pub rank_columns: RankColumns,
+    pub delimiter: u8,
+    // cell values (compared case-insensitively) marking a skipped rank
+    pub blank_markers: Vec<String>,
+    // cell values (compared case-insensitively) marking an overvote
+    pub overvote_markers: Vec<String>,
+    // whether names missing from the registry are registered as new
+    // candidates (e.g. write-ins) or logged as errors
+    pub register_new_candidates: bool,
+    // how skipped ranks, overvotes and repeated rankings are handled
+    pub ballot_rules: BallotRules
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions {
+            rank_columns: RankColumns::Prefix("Choice".to_string()),
+            delimiter: b',',
+            blank_markers: vec![
+                "".to_string(), "undervote".to_string(), "skipped".to_string()
+            ],
+            overvote_markers: vec!["overvote".to_string()],
+            register_new_candidates: true,
+            ballot_rules: BallotRules::default()
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum CsvErrors {
+    Csv(::csv::Error),
+    MissingColumn(String),
+    NoRankColumns
+}
+
+impl fmt::Display for CsvErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CsvErrors::Csv(err) => write!(f, "CSV error: {}", err),
+            CsvErrors::MissingColumn(column) => {
+                write!(f, "Missing rank column {:?}", column)
+            },
+            CsvErrors::NoRankColumns => write!(f, "No rank columns found"),
+        }
+    }
+}
+
+impl From<::csv::Error> for CsvErrors {
+    fn from(err: ::csv::Error) -> Self {
+        CsvErrors::Csv(err)
+    }
+}
+
+#[derive(Debug)]
+pub enum CsvRowErrors {
+    Csv(::csv::Error),
+    UnknownCandidate(String)
+}
+
+impl fmt::Display for CsvRowErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CsvRowErrors::Csv(err) => write!(f, "{}", err),
+            CsvRowErrors::UnknownCandidate(name) => {
+                write!(f, "unknown candidate {:?}", name)
+            },
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct CsvRowError {
+    // 1-based line number of the row in the file
+    pub line: u64,
+    pub error: CsvRowErrors
+}
+
+#[derive(Debug, Default)]
+pub struct CsvImportLog {
+    // number of ballots inserted into the trie
+    pub num_ballots: u64,
+    // number of inserted ballots that were cut short at an overvote
+    pub num_overvotes: u64,
+    // inserted ballots that were cut short for any reason (see
+    // tabulate_rctab_with_exhausted_ballots)
+    pub exhausted_ballots: Vec<ExhaustedBallot<u32>>,
+    // rows that were skipped, and why
+    pub errors: Vec<CsvRowError>
+}
+
+fn find_rank_columns(
+    headers: &::csv::StringRecord, rank_columns: &RankColumns
+) -> Result<Vec<usize>, CsvErrors> {
+    let columns: Vec<usize> = match rank_columns {
+        RankColumns::Named(names) => names.iter().map(|name| {
+            headers.iter().position(|header| header.trim() == name)
+                .ok_or_else(|| CsvErrors::MissingColumn(name.clone()))
+        }).collect::<Result<_, _>>()?,
+        RankColumns::Prefix(prefix) => headers.iter().enumerate()
+            .filter(|(_, header)| header.trim().starts_with(prefix.as_str()))
+            .map(|(index, _)| index)
+            .collect(),
+        RankColumns::Indexes(indexes) => {
+            if let Some(index) = indexes.iter().find(|index| **index >= headers.len()) {
+                return Err(CsvErrors::MissingColumn(index.to_string()));
+            }
+            indexes.clone()
+        }
+    };
+    match columns.is_empty() {
+        true => Err(CsvErrors::NoRankColumns),
+        false => Ok(columns)
+    }
+}
+
+fn has_marker(markers: &[String], value: &str) -> bool {
+    markers.iter().any(|marker| marker.eq_ignore_ascii_case(value))
+}
+
+fn to_vote(
+    record: &::csv::StringRecord, columns: &[usize], options: &CsvOptions,
+    registry: &mut CandidateRegistry
+) -> Result<(RankedVote, Option<ExhaustionReasons>), CsvRowErrors> {
+    /*
+    reads rank columns in order, applying the ballot rules to skipped
+    ranks, overvotes and repeated rankings. Ballots ranking nobody
+    abstain. Also returns why the ballot was cut short, if it was
+    */
+    let mut marks = Vec::with_capacity(columns.len());
+    for column in columns {
+        let value = record.get(*column).unwrap_or("").trim();
+        if has_marker(&options.blank_markers, value) {
+            marks.push(RankMark::Skipped);
+            continue;
+        }
+        if has_marker(&options.overvote_markers, value) {
+            marks.push(RankMark::Overvote);
+            continue;
+        }
+
+        let candidate = match options.register_new_candidates {
+            true => registry.register(value),
+            false => registry.get_id(value).ok_or_else(|| {
+                CsvRowErrors::UnknownCandidate(value.to_string())
+            })?
+        };
+        marks.push(RankMark::Candidate(candidate));
+    }
+    Ok(options.ballot_rules.interpret(&marks))
+}
+
+pub fn read_csv_ballots<R: Read>(
+    reader: R, options: &CsvOptions, registry: &mut CandidateRegistry,
+    rcv: &mut RankedChoiceVoteTrie
+) -> Result<CsvImportLog, CsvErrors> {
+    /*
+    streams CSV rows into the trie one ballot at a time. Only
+    problems with the file as a whole (e.g. missing rank columns)
+    are returned as errors, bad rows are skipped and logged instead.
+    Every candidate in the registry is declared in the trie
+    */
+    let mut csv_reader = ::csv::ReaderBuilder::new()
+        .delimiter(options.delimiter)
+        .flexible(true)
+        .from_reader(reader);
+    let headers = csv_reader.headers()?.clone();
+    let columns = find_rank_columns(&headers, &options.rank_columns)?;
+
+    let mut log = CsvImportLog::default();
+    let mut record = ::csv::StringRecord::new();
+    loop {
+        let line = csv_reader.position().line();
+        match csv_reader.read_record(&mut record) {
+            Ok(false) => break,
+            Ok(true) => {}
+            Err(error) => {
+                let line = error.position().map_or(line, |position| position.line());
+                log.errors.push(CsvRowError { line, error: CsvRowErrors::Csv(error) });
+                continue;
+            }
+        }
+        let line = record.position().map_or(line, |position| position.line());
+
+        match to_vote(&record, &columns, options, registry) {
+            Ok((vote, exhaustion_reason)) => {
+                log.num_ballots += 1;
+                if exhaustion_reason == Some(ExhaustionReasons::Overvote) {
+                    log.num_overvotes += 1;
+                }
+                if let Some(exhaustion_reason) = exhaustion_reason {
+                    log.exhausted_ballots.push((vote.clone(), exhaustion_reason));
+                }
+                rcv.insert_vote(vote);
+            }
+            Err(error) => log.errors.push(CsvRowError { line, error })
+        }
+    }
+
+    rcv.declare_candidates(&registry.candidate_ids());
+    Ok(log)
+}
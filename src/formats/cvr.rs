@@ -0,0 +1,331 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::io::Read;
+use serde::Deserialize;
+use crate::GenericRankedChoiceVoteTrie;
+use crate::rctab::{BallotRules, ExhaustedBallot, ExhaustionReasons, RankMark};
+use crate::vote::{GenericRankedVote, SpecialVotes, VoteErrors};
+
+/*
+reads ranked contests out of NIST SP 1500-103 Cast Vote Record
+reports in JSON. Each CVR lists the contests on the ballot, and for
+each contest the selections marked at each rank:
+
+{"Election": [{"Candidate": [...], "Contest": [...]}],
+ "CVR": [{"@id": "cvr-1", "CurrentSnapshotId": "snap-1",
+          "CVRSnapshot": [{"@id": "snap-1", "CVRContest": [{
+              "ContestId": "contest-1",
+              "CVRContestSelection": [{"ContestSelectionId": "cs-1",
+                  "SelectionPosition": [{"Rank": 1, "HasIndication": "yes"}]
+              }]
+          }]}]
+ }]}
+
+Only fields used for ranked choice tabulation are read. Candidates
+are identified by the @id of their candidate selection's candidate
+(or the selection's own @id for write-in selections)
+*/
+
+#[derive(Debug)]
+pub enum CvrErrors {
+    Json(serde_json::Error),
+    UnknownContest(String),
+    UnknownContestSelection { cvr_id: String, selection_id: String },
+    MissingSnapshot { cvr_id: String },
+    // ranks start at 1 and go up to the number of contest selections
+    InvalidRank { cvr_id: String, rank: u32 },
+    InvalidVote(VoteErrors)
+}
+
+impl fmt::Display for CvrErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CvrErrors::Json(err) => write!(f, "Invalid CVR JSON: {}", err),
+            CvrErrors::UnknownContest(contest_id) => {
+                write!(f, "No contest with id {:?}", contest_id)
+            },
+            CvrErrors::UnknownContestSelection { cvr_id, selection_id } => {
+                write!(
+                    f, "CVR {}: unknown contest selection {:?}",
+                    cvr_id, selection_id
+                )
+            },
+            CvrErrors::MissingSnapshot { cvr_id } => {
+                write!(f, "CVR {}: current snapshot not found", cvr_id)
+            },
+            CvrErrors::InvalidRank { cvr_id, rank } => {
+                write!(f, "CVR {}: rank {} is out of range", cvr_id, rank)
+            },
+            CvrErrors::InvalidVote(err) => write!(f, "Invalid vote: {}", err),
+        }
+    }
+}
+
+impl From<serde_json::Error> for CvrErrors {
+    fn from(err: serde_json::Error) -> Self {
+        CvrErrors::Json(err)
+    }
+}
+
+impl From<VoteErrors> for CvrErrors {
+    fn from(err: VoteErrors) -> Self {
+        CvrErrors::InvalidVote(err)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct CvrReport {
+    #[serde(default)]
+    election: Vec<Election>,
+    #[serde(default, rename = "CVR")]
+    cvrs: Vec<CastVoteRecord>
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Election {
+    #[serde(default)]
+    candidate: Vec<Candidate>,
+    #[serde(default)]
+    contest: Vec<Contest>
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Candidate {
+    #[serde(rename = "@id")]
+    id: String,
+    name: Option<String>
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Contest {
+    #[serde(rename = "@id")]
+    id: String,
+    #[serde(default)]
+    contest_selection: Vec<ContestSelection>
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ContestSelection {
+    #[serde(rename = "@id")]
+    id: String,
+    #[serde(default)]
+    candidate_ids: Vec<String>,
+    #[serde(default)]
+    is_write_in: bool
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct CastVoteRecord {
+    #[serde(rename = "@id", default)]
+    id: String,
+    current_snapshot_id: Option<String>,
+    #[serde(default, rename = "CVRSnapshot")]
+    snapshots: Vec<Snapshot>
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Snapshot {
+    #[serde(rename = "@id", default)]
+    id: String,
+    #[serde(default, rename = "CVRContest")]
+    contests: Vec<CvrContestData>
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct CvrContestData {
+    contest_id: String,
+    #[serde(default, rename = "CVRContestSelection")]
+    selections: Vec<CvrContestSelection>
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct CvrContestSelection {
+    contest_selection_id: Option<String>,
+    #[serde(default)]
+    selection_position: Vec<SelectionPosition>
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct SelectionPosition {
+    rank: Option<u32>,
+    has_indication: Option<String>,
+    is_allocable: Option<String>
+}
+
+impl SelectionPosition {
+    fn is_marked(&self) -> bool {
+        // unmarked or unallocable positions don't count as a ranking
+        self.has_indication.as_deref() != Some("no") &&
+            self.is_allocable.as_deref() != Some("no")
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CvrContest {
+    pub contest_id: String,
+    // candidate names by candidate id (write-in selections are
+    // named "Write-in")
+    pub candidate_names: BTreeMap<String, String>,
+    // one vote per CVR that includes the contest
+    pub votes: Vec<GenericRankedVote<String>>,
+    // number of votes that were cut short at an overvoted rank
+    pub num_overvotes: u64,
+    // votes that were cut short for any reason (see
+    // tabulate_rctab_with_exhausted_ballots)
+    pub exhausted_ballots: Vec<ExhaustedBallot<String>>
+}
+
+impl CvrContest {
+    pub fn to_trie(&self) -> GenericRankedChoiceVoteTrie<String> {
+        // every candidate in the contest is declared, ranked or not
+        let mut rcv = GenericRankedChoiceVoteTrie::new();
+        let candidates: Vec<String> =
+            self.candidate_names.keys().cloned().collect();
+        rcv.declare_candidates(&candidates);
+        rcv.insert_votes(self.votes.clone());
+        rcv
+    }
+}
+
+fn to_vote(
+    cvr_id: &str, contest: &CvrContestData, max_rank: u32,
+    selection_candidates: &HashMap<&str, &str>, ballot_rules: &BallotRules
+) -> Result<(GenericRankedVote<String>, Option<ExhaustionReasons>), CvrErrors> {
+    /*
+    reads rankings in rank order, applying the ballot rules to skipped
+    ranks, repeated rankings and ranks marked for more than one
+    candidate (overvotes). Ballots ranking nobody abstain. Also
+    returns why the ballot was cut short, if it was
+    */
+    let mut ranks: BTreeMap<u32, HashSet<&str>> = BTreeMap::new();
+    for selection in &contest.selections {
+        let Some(selection_id) = &selection.contest_selection_id else {
+            continue
+        };
+        let candidate = *selection_candidates.get(selection_id.as_str())
+            .ok_or_else(|| CvrErrors::UnknownContestSelection {
+                cvr_id: cvr_id.to_string(), selection_id: selection_id.clone()
+            })?;
+        for position in &selection.selection_position {
+            if !position.is_marked() { continue }
+            // single choice positions without a rank count as a first choice
+            let rank = position.rank.unwrap_or(1);
+            if rank == 0 || rank > max_rank {
+                return Err(CvrErrors::InvalidRank {
+                    cvr_id: cvr_id.to_string(), rank
+                });
+            }
+            ranks.entry(rank).or_default().insert(candidate);
+        }
+    }
+
+    let mut marks: Vec<RankMark<String>> = Vec::new();
+    let mut next_rank = 1;
+    for (rank, candidates) in &ranks {
+        // ranks missing from the CVR were skipped
+        marks.extend((next_rank..*rank).map(|_| RankMark::Skipped));
+        marks.push(match candidates.len() {
+            1 => {
+                let candidate = candidates.iter().next()
+                    .expect("ranks are non-empty");
+                RankMark::Candidate(candidate.to_string())
+            },
+            _ => RankMark::Overvote
+        });
+        next_rank = rank + 1;
+    }
+    let (rankings, exhaustion_reason) = ballot_rules.interpret_rankings(&marks);
+    let special_vote = match rankings.is_empty() {
+        true => Some(SpecialVotes::ABSTAIN),
+        false => None
+    };
+    let vote = GenericRankedVote::new(rankings, special_vote)?;
+    Ok((vote, exhaustion_reason))
+}
+
+pub fn read_cvr_report<R: Read>(
+    reader: R, contest_id: &str
+) -> Result<CvrContest, CvrErrors> {
+    /*
+    converts the current snapshot of each CVR in a cast vote record
+    report into a ranked vote for the given contest. CVRs that don't
+    include the contest are left out
+    */
+    read_cvr_report_with_rules(reader, contest_id, &BallotRules::default())
+}
+
+pub fn read_cvr_report_with_rules<R: Read>(
+    reader: R, contest_id: &str, ballot_rules: &BallotRules
+) -> Result<CvrContest, CvrErrors> {
+    let report: CvrReport = serde_json::from_reader(reader)?;
+    let candidate_names: HashMap<&str, &str> = report.election.iter()
+        .flat_map(|election| &election.candidate)
+        .map(|candidate| {
+            let name = candidate.name.as_deref().unwrap_or(&candidate.id);
+            (candidate.id.as_str(), name)
+        }).collect();
+    let contest = report.election.iter()
+        .flat_map(|election| &election.contest)
+        .find(|contest| contest.id == contest_id)
+        .ok_or_else(|| CvrErrors::UnknownContest(contest_id.to_string()))?;
+
+    let max_rank = contest.contest_selection.len() as u32;
+    let mut selection_candidates: HashMap<&str, &str> = HashMap::new();
+    let mut contest_candidate_names = BTreeMap::new();
+    for selection in &contest.contest_selection {
+        let candidate_id = match selection.is_write_in {
+            true => selection.id.as_str(),
+            false => selection.candidate_ids.first()
+                .map_or(selection.id.as_str(), String::as_str)
+        };
+        let name = match selection.is_write_in {
+            true => "Write-in",
+            false => candidate_names.get(candidate_id).copied()
+                .unwrap_or(candidate_id)
+        };
+        selection_candidates.insert(&selection.id, candidate_id);
+        contest_candidate_names.insert(candidate_id.to_string(), name.to_string());
+    }
+
+    let mut votes = Vec::new();
+    let mut num_overvotes = 0;
+    let mut exhausted_ballots = Vec::new();
+    for cvr in &report.cvrs {
+        let snapshot = match &cvr.current_snapshot_id {
+            Some(snapshot_id) => cvr.snapshots.iter()
+                .find(|snapshot| &snapshot.id == snapshot_id),
+            None => cvr.snapshots.last()
+        }.ok_or_else(|| CvrErrors::MissingSnapshot { cvr_id: cvr.id.clone() })?;
+
+        let cvr_contest = snapshot.contests.iter()
+            .find(|cvr_contest| cvr_contest.contest_id == contest_id);
+        let Some(cvr_contest) = cvr_contest else { continue };
+        let (vote, exhaustion_reason) = to_vote(
+            &cvr.id, cvr_contest, max_rank, &selection_candidates,
+            ballot_rules
+        )?;
+        if exhaustion_reason == Some(ExhaustionReasons::Overvote) {
+            num_overvotes += 1;
+        }
+        if let Some(exhaustion_reason) = exhaustion_reason {
+            exhausted_ballots.push((vote.clone(), exhaustion_reason));
+        }
+        votes.push(vote);
+    }
+
+    Ok(CvrContest {
+        contest_id: contest_id.to_string(),
+        candidate_names: contest_candidate_names, votes, num_overvotes,
+        exhausted_ballots
+    })
+}
@@ -0,0 +1,64 @@
+/*
+readers and writers for ballot interchange formats used by other
+election tools. Each format lives in its own module with its own
+error type, and parse errors report the line they were found on
+*/
+use std::io::{self, BufRead};
+use crate::CandidateId;
+use crate::vote::{GenericRankedVote, SpecialVotes, VoteErrors};
+
+pub mod blt;
+pub mod preflib;
+pub mod abif;
+#[cfg(feature = "cvr")]
+pub mod cvr;
+#[cfg(feature = "csv")]
+pub mod csv;
+
+pub(crate) struct LineReader<R> {
+    reader: R,
+    line_number: usize
+}
+
+impl<R: BufRead> LineReader<R> {
+    pub(crate) fn new(reader: R) -> Self {
+        LineReader { reader, line_number: 0 }
+    }
+
+    pub(crate) fn next_line(&mut self) -> io::Result<Option<(usize, String)>> {
+        // returns the next non-blank line (trimmed) and its 1-based line number
+        loop {
+            let mut line = String::new();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Ok(None);
+            }
+            self.line_number += 1;
+            let line = line.trim();
+            if !line.is_empty() {
+                return Ok(Some((self.line_number, line.to_string())));
+            }
+        }
+    }
+}
+
+pub(crate) fn untied_vote<C: CandidateId>(
+    groups: &[Vec<C>]
+) -> Result<(GenericRankedVote<C>, bool), VoteErrors> {
+    /*
+    converts groups of equally ranked candidates into a ranked vote.
+    Ranked votes can't hold ties, so rankings are cut short at the
+    first tie: a voter that can't choose between candidates has no
+    further preference to transfer. Ballots that start with a tie (or
+    rank nobody) abstain. Also returns whether rankings were cut short
+    */
+    let rankings: Vec<C> = groups.iter()
+        .take_while(|group| group.len() == 1)
+        .map(|group| group[0].clone())
+        .collect();
+    let is_truncated = rankings.len() < groups.len();
+    let special_vote = match rankings.is_empty() {
+        true => Some(SpecialVotes::ABSTAIN),
+        false => None
+    };
+    Ok((GenericRankedVote::new(rankings, special_vote)?, is_truncated))
+}
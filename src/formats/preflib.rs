@@ -0,0 +1,295 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{self, BufRead};
+use crate::RankedChoiceVoteTrie;
+use crate::formats::{untied_vote, LineReader};
+use crate::vote::{RankedVote, VoteErrors};
+
+/*
+PrefLib election files start with a "# KEY: value" metadata header,
+followed by one line per distinct preference order:
+
+# DATA TYPE: toi
+# NUMBER ALTERNATIVES: 3
+# ALTERNATIVE NAME 1: Alice
+...
+12: 1,2,3
+5: 2,{1,3}
+
+i.e. the number of voters casting the order, then the alternatives
+from most to least preferred, with tied alternatives in braces.
+Alternatives are numbered from 1 and used directly as candidate ids
+*/
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum PreflibDataType {
+    // strict orders over all alternatives
+    Soc,
+    // strict orders over some of the alternatives
+    Soi,
+    // orders with ties over all alternatives
+    Toc,
+    // orders with ties over some of the alternatives
+    Toi
+}
+
+impl PreflibDataType {
+    pub fn allows_ties(&self) -> bool {
+        matches!(self, PreflibDataType::Toc | PreflibDataType::Toi)
+    }
+
+    pub fn is_complete(&self) -> bool {
+        matches!(self, PreflibDataType::Soc | PreflibDataType::Toc)
+    }
+
+    fn from_name(name: &str) -> Option<PreflibDataType> {
+        match name.to_ascii_lowercase().as_str() {
+            "soc" => Some(PreflibDataType::Soc),
+            "soi" => Some(PreflibDataType::Soi),
+            "toc" => Some(PreflibDataType::Toc),
+            "toi" => Some(PreflibDataType::Toi),
+            _ => None
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum PreflibErrors {
+    Io(io::Error),
+    MissingHeader(&'static str),
+    InvalidHeader { line: usize },
+    UnsupportedDataType { line: usize, data_type: String },
+    InvalidNumber { line: usize, token: String },
+    InvalidOrder { line: usize },
+    ZeroCount { line: usize },
+    InvalidAlternative { line: usize, alternative: u32 },
+    // ties in a SOC or SOI file
+    TiedRankings { line: usize },
+    // an order in a SOC or TOC file that leaves out alternatives
+    IncompleteOrder { line: usize },
+    InvalidVote { line: usize, error: VoteErrors }
+}
+
+impl PreflibErrors {
+    pub fn line(&self) -> Option<usize> {
+        // the (1-based) line number the error was found on, if any
+        match self {
+            PreflibErrors::InvalidHeader { line } |
+            PreflibErrors::UnsupportedDataType { line, .. } |
+            PreflibErrors::InvalidNumber { line, .. } |
+            PreflibErrors::InvalidOrder { line } |
+            PreflibErrors::ZeroCount { line } |
+            PreflibErrors::InvalidAlternative { line, .. } |
+            PreflibErrors::TiedRankings { line } |
+            PreflibErrors::IncompleteOrder { line } |
+            PreflibErrors::InvalidVote { line, .. } => Some(*line),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Display for PreflibErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PreflibErrors::Io(err) => write!(f, "IO error: {}", err),
+            PreflibErrors::MissingHeader(key) => {
+                write!(f, "Missing {} header", key)
+            },
+            PreflibErrors::InvalidHeader { line } => {
+                write!(f, "line {}: expected \"# KEY: value\"", line)
+            },
+            PreflibErrors::UnsupportedDataType { line, data_type } => {
+                write!(f, "line {}: unsupported data type {:?}", line, data_type)
+            },
+            PreflibErrors::InvalidNumber { line, token } => {
+                write!(f, "line {}: invalid number {:?}", line, token)
+            },
+            PreflibErrors::InvalidOrder { line } => {
+                write!(f, "line {}: expected \"count: order\"", line)
+            },
+            PreflibErrors::ZeroCount { line } => {
+                write!(f, "line {}: order count must be at least 1", line)
+            },
+            PreflibErrors::InvalidAlternative { line, alternative } => {
+                write!(f, "line {}: invalid alternative {}", line, alternative)
+            },
+            PreflibErrors::TiedRankings { line } => {
+                write!(f, "line {}: ties are not allowed in strict orders", line)
+            },
+            PreflibErrors::IncompleteOrder { line } => {
+                write!(f, "line {}: order does not rank every alternative", line)
+            },
+            PreflibErrors::InvalidVote { line, error } => {
+                write!(f, "line {}: {}", line, error)
+            },
+        }
+    }
+}
+
+impl From<io::Error> for PreflibErrors {
+    fn from(err: io::Error) -> Self {
+        PreflibErrors::Io(err)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PreflibMetadata {
+    pub data_type: PreflibDataType,
+    pub num_alternatives: u32,
+    pub title: Option<String>,
+    pub alternative_names: BTreeMap<u32, String>,
+    // every header field in file order, including the ones above
+    pub headers: Vec<(String, String)>
+}
+
+#[derive(Clone, Debug)]
+pub struct PreflibElection {
+    pub metadata: PreflibMetadata,
+    pub trie: RankedChoiceVoteTrie,
+    // number of voters whose order was cut short at a tie
+    pub num_truncated_votes: u64
+}
+
+fn parse_number<T: std::str::FromStr>(
+    line: usize, token: &str
+) -> Result<T, PreflibErrors> {
+    let token = token.trim();
+    token.parse().map_err(|_| PreflibErrors::InvalidNumber {
+        line, token: token.to_string()
+    })
+}
+
+fn parse_order(line: usize, text: &str) -> Result<Vec<Vec<u32>>, PreflibErrors> {
+    // splits "1,{2,3},4" into groups of equally ranked alternatives
+    let mut groups = Vec::new();
+    let mut rest = text.trim();
+    while !rest.is_empty() {
+        let (group, remainder) = match rest.strip_prefix('{') {
+            Some(tied) => {
+                let end = tied.find('}')
+                    .ok_or(PreflibErrors::InvalidOrder { line })?;
+                let group = tied[..end].split(',')
+                    .map(|token| parse_number(line, token))
+                    .collect::<Result<Vec<u32>, _>>()?;
+                (group, &tied[end + 1..])
+            }
+            None => {
+                let end = rest.find(',').unwrap_or(rest.len());
+                (vec![parse_number(line, &rest[..end])?], &rest[end..])
+            }
+        };
+        groups.push(group);
+
+        let remainder = remainder.trim_start();
+        rest = match remainder.strip_prefix(',') {
+            Some(remainder) => remainder.trim_start(),
+            None if remainder.is_empty() => remainder,
+            None => return Err(PreflibErrors::InvalidOrder { line })
+        };
+    }
+    Ok(groups)
+}
+
+fn to_vote(
+    line: usize, groups: &[Vec<u32>], metadata: &PreflibMetadata
+) -> Result<(RankedVote, bool), PreflibErrors> {
+    // checks the order against the data type before cutting it short
+    // at its first tie (see untied_vote)
+    let data_type = metadata.data_type;
+    let mut num_ranked = 0;
+    for group in groups {
+        for &alternative in group {
+            if alternative < 1 || alternative > metadata.num_alternatives {
+                return Err(PreflibErrors::InvalidAlternative {
+                    line, alternative
+                });
+            }
+        }
+        if group.len() > 1 && !data_type.allows_ties() {
+            return Err(PreflibErrors::TiedRankings { line });
+        }
+        num_ranked += group.len();
+    }
+    if data_type.is_complete() && num_ranked != metadata.num_alternatives as usize {
+        return Err(PreflibErrors::IncompleteOrder { line });
+    }
+    untied_vote(groups).map_err(|error| PreflibErrors::InvalidVote { line, error })
+}
+
+fn build_metadata(
+    headers: Vec<(usize, String, String)>
+) -> Result<PreflibMetadata, PreflibErrors> {
+    let mut data_type = None;
+    let mut num_alternatives = None;
+    let mut title = None;
+    let mut alternative_names = BTreeMap::new();
+
+    for (line, key, value) in &headers {
+        let line = *line;
+        if key == "DATA TYPE" {
+            data_type = Some(PreflibDataType::from_name(value).ok_or_else(
+                || PreflibErrors::UnsupportedDataType {
+                    line, data_type: value.clone()
+                }
+            )?);
+        } else if key == "NUMBER ALTERNATIVES" {
+            num_alternatives = Some(parse_number(line, value)?);
+        } else if key == "TITLE" {
+            title = Some(value.clone());
+        } else if let Some(alternative) = key.strip_prefix("ALTERNATIVE NAME ") {
+            alternative_names.insert(
+                parse_number(line, alternative)?, value.clone()
+            );
+        }
+    }
+
+    Ok(PreflibMetadata {
+        data_type: data_type.ok_or(PreflibErrors::MissingHeader("DATA TYPE"))?,
+        num_alternatives: num_alternatives
+            .ok_or(PreflibErrors::MissingHeader("NUMBER ALTERNATIVES"))?,
+        title, alternative_names,
+        headers: headers.into_iter().map(|(_, key, value)| (key, value)).collect()
+    })
+}
+
+pub fn read_preflib<R: BufRead>(
+    reader: R
+) -> Result<PreflibElection, PreflibErrors> {
+    /*
+    loads a PrefLib SOC, SOI, TOC or TOI file into a trie, with
+    every alternative declared as a candidate. The data type is taken
+    from the file's DATA TYPE header
+    */
+    let mut lines = LineReader::new(reader);
+    let mut headers = Vec::new();
+    let mut next_line = lines.next_line()?;
+    while let Some((line, text)) = &next_line {
+        let Some(header) = text.strip_prefix('#') else { break };
+        let (key, value) = header.split_once(':')
+            .ok_or(PreflibErrors::InvalidHeader { line: *line })?;
+        headers.push((*line, key.trim().to_string(), value.trim().to_string()));
+        next_line = lines.next_line()?;
+    }
+    let metadata = build_metadata(headers)?;
+
+    let mut trie = RankedChoiceVoteTrie::new();
+    let alternatives: Vec<u32> = (1..=metadata.num_alternatives).collect();
+    trie.declare_candidates(&alternatives);
+    let mut num_truncated_votes = 0;
+
+    while let Some((line, text)) = next_line {
+        let (count, order) = text.split_once(':')
+            .ok_or(PreflibErrors::InvalidOrder { line })?;
+        let count: u64 = parse_number(line, count)?;
+        if count == 0 {
+            return Err(PreflibErrors::ZeroCount { line });
+        }
+        let groups = parse_order(line, order)?;
+        let (vote, is_truncated) = to_vote(line, &groups, &metadata)?;
+        if is_truncated { num_truncated_votes += count; }
+        trie.insert_weighted_vote(vote, count);
+        next_line = lines.next_line()?;
+    }
+
+    Ok(PreflibElection { metadata, trie, num_truncated_votes })
+}
@@ -0,0 +1,215 @@
+use std::collections::{HashMap, HashSet};
+use crate::{CandidateId, EliminationStrategies, GenericRankedChoiceVoteTrie};
+use crate::arena::GenericArenaVoteTrie;
+use crate::tabulation::{TabulationSource, Tabulator};
+use crate::traversal::TrieTraversal;
+use crate::vote::{GenericVoteValues, SpecialVotes};
+
+// node keys: special votes take the first values, candidates are
+// stored as an offset into the (sorted) candidate table
+pub(crate) const WITHHOLD_KEY: u32 = 0;
+pub(crate) const ABSTAIN_KEY: u32 = 1;
+pub(crate) const CANDIDATE_KEY_OFFSET: u32 = 2;
+
+pub(crate) fn decode_key(key: u32) -> GenericVoteValues<u32> {
+    match key {
+        WITHHOLD_KEY => GenericVoteValues::SpecialVote(SpecialVotes::WITHHOLD),
+        ABSTAIN_KEY => GenericVoteValues::SpecialVote(SpecialVotes::ABSTAIN),
+        _ => GenericVoteValues::Candidate(key - CANDIDATE_KEY_OFFSET)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct FrozenNode {
+    // encoded vote value on the edge leading into this node
+    pub key: u32,
+    // number of nodes in the subtree rooted at this node (itself included)
+    pub subtree_len: u32,
+    pub num_votes: u64
+}
+
+#[derive(Clone, Debug)]
+pub struct GenericFrozenTrie<C> {
+    /*
+    immutable snapshot of a ballot trie for repeated tabulation.
+    Nodes are stored depth-first in a single vector with children
+    sorted, so that every subtree is a contiguous run of nodes, and
+    candidates are interned into dense indices (in sorted candidate
+    order) so that tabulation works on plain integers
+    */
+    pub(crate) candidates: Vec<C>,
+    pub(crate) nodes: Vec<FrozenNode>,
+    pub(crate) rank_counts: HashMap<u32, Vec<u64>>,
+    pub(crate) elimination_strategy: EliminationStrategies,
+    unique_candidates: HashSet<u32>,
+    pub(crate) excluded_candidates: HashSet<u32>
+}
+
+pub type FrozenTrie = GenericFrozenTrie<u32>;
+
+fn freeze_node<'a, C: CandidateId, T: TrieTraversal<C>>(
+    trie: &'a T, node: T::Node<'a>, key: u32,
+    candidate_indexes: &HashMap<&C, u32>, nodes: &mut Vec<FrozenNode>
+) {
+    let position = nodes.len();
+    nodes.push(FrozenNode {
+        key, subtree_len: 0, num_votes: trie.node_votes(node)
+    });
+
+    let mut children: Vec<(u32, T::Node<'a>)> = Vec::new();
+    trie.visit_children(node, |vote_value, child| {
+        let child_key = match vote_value {
+            GenericVoteValues::SpecialVote(SpecialVotes::WITHHOLD) => {
+                WITHHOLD_KEY
+            },
+            GenericVoteValues::SpecialVote(SpecialVotes::ABSTAIN) => {
+                ABSTAIN_KEY
+            },
+            GenericVoteValues::Candidate(candidate) => {
+                CANDIDATE_KEY_OFFSET + candidate_indexes[candidate]
+            }
+        };
+        children.push((child_key, child));
+    });
+    children.sort_by_key(|(child_key, _)| *child_key);
+
+    for (child_key, child) in children {
+        freeze_node(trie, child, child_key, candidate_indexes, nodes);
+    }
+    nodes[position].subtree_len = u32::try_from(nodes.len() - position)
+        .expect("frozen trie cannot hold more than u32::MAX nodes");
+}
+
+fn freeze_trie<C: CandidateId, T: TrieTraversal<C>>(
+    trie: &T, unique_candidates: &HashSet<C>,
+    rank_counts: &HashMap<C, Vec<u64>>,
+    elimination_strategy: EliminationStrategies,
+    excluded_candidates: &HashSet<C>
+) -> GenericFrozenTrie<C> {
+    let mut candidates: Vec<C> = unique_candidates.iter().cloned().collect();
+    candidates.sort();
+    let candidate_indexes: HashMap<&C, u32> = candidates.iter()
+        .enumerate().map(|(index, candidate)| {
+            let index = u32::try_from(index)
+                .expect("too many candidates to freeze trie");
+            (candidate, index)
+        }).collect();
+
+    let mut nodes = Vec::new();
+    freeze_node(trie, trie.root_node(), 0, &candidate_indexes, &mut nodes);
+
+    let rank_counts = rank_counts.iter()
+        .map(|(candidate, rank_counts)| {
+            (candidate_indexes[candidate], rank_counts.clone())
+        }).collect();
+    let excluded_candidates = excluded_candidates.iter()
+        .filter_map(|candidate| candidate_indexes.get(candidate).copied())
+        .collect();
+
+    GenericFrozenTrie {
+        unique_candidates: (0..candidates.len() as u32).collect(),
+        candidates, nodes, rank_counts, elimination_strategy,
+        excluded_candidates
+    }
+}
+
+impl<C: CandidateId> GenericRankedChoiceVoteTrie<C> {
+    pub fn freeze(&self) -> GenericFrozenTrie<C> {
+        /*
+        creates a read-optimised snapshot of the trie for tabulating
+        many times over. The snapshot keeps the trie's elimination
+        strategy and excluded candidates, which can be changed on the
+        snapshot without affecting the trie
+        */
+        freeze_trie(
+            self, &self.unique_candidates, &self.rank_counts,
+            self.elimination_strategy, &self.excluded_candidates
+        )
+    }
+}
+
+impl<C: CandidateId> GenericArenaVoteTrie<C> {
+    pub fn freeze(&self) -> GenericFrozenTrie<C> {
+        freeze_trie(
+            self, &self.unique_candidates, &self.rank_counts,
+            self.elimination_strategy, &self.excluded_candidates
+        )
+    }
+}
+
+pub(crate) fn candidate_index<C: CandidateId>(
+    candidates: &[C], candidate: &C
+) -> Option<u32> {
+    candidates.binary_search(candidate).ok().map(|index| index as u32)
+}
+
+impl<C: CandidateId> GenericFrozenTrie<C> {
+    pub fn get_num_votes(&self) -> u64 {
+        self.nodes[0].num_votes
+    }
+
+    pub fn get_num_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn candidates(&self) -> &[C] {
+        &self.candidates
+    }
+}
+
+impl<C: CandidateId> TabulationSource<C> for GenericFrozenTrie<C> {
+    // candidates are tabulated by their index in the candidate table
+    type Key = u32;
+    type Trie = Self;
+
+    fn tabulator(&self) -> Tabulator<'_, u32, Self> {
+        Tabulator {
+            trie: self,
+            elimination_strategy: self.elimination_strategy,
+            unique_candidates: &self.unique_candidates,
+            excluded_candidates: &self.excluded_candidates,
+            rank_counts: Some(&self.rank_counts)
+        }
+    }
+
+    fn elimination_strategy_mut(&mut self) -> &mut EliminationStrategies {
+        &mut self.elimination_strategy
+    }
+
+    fn excluded_candidates_mut(&mut self) -> &mut HashSet<u32> {
+        &mut self.excluded_candidates
+    }
+
+    fn to_key(&self, candidate: &C) -> Option<u32> {
+        candidate_index(&self.candidates, candidate)
+    }
+
+    fn to_candidate(&self, index: &u32) -> C {
+        self.candidates[*index as usize].clone()
+    }
+}
+
+impl<C> TrieTraversal<u32> for GenericFrozenTrie<C> {
+    type Node<'a> = u32 where C: 'a;
+
+    fn root_node(&self) -> u32 {
+        0
+    }
+
+    fn node_votes(&self, node: u32) -> u64 {
+        self.nodes[node as usize].num_votes
+    }
+
+    fn visit_children<'a, F>(&'a self, node: u32, mut visit: F)
+    where F: FnMut(&GenericVoteValues<u32>, Self::Node<'a>) {
+        // the first child directly follows its parent, and each
+        // following sibling comes right after the previous subtree
+        let subtree_end = node + self.nodes[node as usize].subtree_len;
+        let mut child = node + 1;
+        while child < subtree_end {
+            let child_node = &self.nodes[child as usize];
+            visit(&decode_key(child_node.key), child);
+            child += child_node.subtree_len;
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use std::fmt::Write;
+
+// minimal JSON document model for writing reports, with object keys
+// kept in insertion order so that output is stable
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum JsonValue {
+    Null,
+    Bool(bool),
+    // number already formatted as JSON
+    Number(String),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>)
+}
+
+impl From<u64> for JsonValue {
+    fn from(value: u64) -> Self {
+        JsonValue::Number(value.to_string())
+    }
+}
+
+impl From<usize> for JsonValue {
+    fn from(value: usize) -> Self {
+        JsonValue::Number(value.to_string())
+    }
+}
+
+impl From<f64> for JsonValue {
+    fn from(value: f64) -> Self {
+        match value.is_finite() {
+            true => JsonValue::Number(value.to_string()),
+            false => JsonValue::Null
+        }
+    }
+}
+
+impl From<bool> for JsonValue {
+    fn from(value: bool) -> Self {
+        JsonValue::Bool(value)
+    }
+}
+
+impl From<&str> for JsonValue {
+    fn from(value: &str) -> Self {
+        JsonValue::String(value.to_string())
+    }
+}
+
+impl From<String> for JsonValue {
+    fn from(value: String) -> Self {
+        JsonValue::String(value)
+    }
+}
+
+impl<T: Into<JsonValue>> From<Option<T>> for JsonValue {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(JsonValue::Null, Into::into)
+    }
+}
+
+pub(crate) fn escape_json(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len() + 2);
+    escaped.push('"');
+    for character in text.chars() {
+        match character {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            character if (character as u32) < 0x20 => {
+                write!(escaped, "\\u{:04x}", character as u32)
+                    .expect("writing to a string cannot fail");
+            }
+            character => escaped.push(character)
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+impl JsonValue {
+    pub fn to_pretty_string(&self) -> String {
+        // two space indents, like JSON.stringify(value, null, 2)
+        let mut output = String::new();
+        self.write_pretty(&mut output, 0);
+        output
+    }
+
+    fn write_pretty(&self, output: &mut String, depth: usize) {
+        let indent = |output: &mut String, depth: usize| {
+            output.push('\n');
+            output.push_str(&"  ".repeat(depth));
+        };
+        match self {
+            JsonValue::Null => output.push_str("null"),
+            JsonValue::Bool(value) => output.push_str(&value.to_string()),
+            JsonValue::Number(value) => output.push_str(value),
+            JsonValue::String(value) => output.push_str(&escape_json(value)),
+            JsonValue::Array(values) => {
+                if values.is_empty() {
+                    output.push_str("[]");
+                    return;
+                }
+                output.push('[');
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 { output.push(','); }
+                    indent(output, depth + 1);
+                    value.write_pretty(output, depth + 1);
+                }
+                indent(output, depth);
+                output.push(']');
+            }
+            JsonValue::Object(entries) => {
+                if entries.is_empty() {
+                    output.push_str("{}");
+                    return;
+                }
+                output.push('{');
+                for (index, (key, value)) in entries.iter().enumerate() {
+                    if index > 0 { output.push(','); }
+                    indent(output, depth + 1);
+                    output.push_str(&escape_json(key));
+                    output.push_str(": ");
+                    value.write_pretty(output, depth + 1);
+                }
+                indent(output, depth);
+                output.push('}');
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pretty_json_keeps_key_order() {
+        let value = JsonValue::Object(vec![
+            ("b".to_string(), JsonValue::from(1u64)),
+            ("a".to_string(), JsonValue::Array(vec![
+                JsonValue::from("x\"y"), JsonValue::Null
+            ])),
+            ("c".to_string(), JsonValue::Object(vec![]))
+        ]);
+        assert_eq!(
+            value.to_pretty_string(),
+            "{\n  \"b\": 1,\n  \"a\": [\n    \"x\\\"y\",\n    null\n  ],\n  \"c\": {}\n}"
+        );
+    }
+}
@@ -1,29 +1,75 @@
-use std::cmp::{min, Ordering, PartialEq};
-use std::collections::{HashMap, HashSet};
+use std::cmp::PartialEq;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use petgraph::graph::{DiGraph, NodeIndex};
-use itertools::{iproduct, Itertools};
+use itertools::Itertools;
 use std::collections::VecDeque;
+use std::collections::hash_map::Entry;
+use std::iter::Sum;
 use petgraph::Direction;
 use petgraph::prelude::EdgeRef;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use scoring::{
+    add_rank_count, build_rank_histogram, dowdall_weight, merge_rank_counts,
+    positional_scores, BigRational, CandidateRankCounts
+};
+use tabulation::{TabulationSource, Tabulator};
+use traversal::TrieTraversal;
+
+pub use traversal::TrieTabulation;
 
 pub use vote::*;
 
 pub mod vote;
+pub mod binary;
+pub mod precincts;
+pub mod ballots;
+pub mod prefix;
+pub mod registry;
+pub mod rctab;
+pub mod reports;
+pub mod sankey;
+pub mod formats;
+pub mod scoring;
+pub mod traversal;
+pub mod arena;
+pub mod frozen;
+pub mod mapped;
+mod tabulation;
+mod json;
+#[cfg(feature = "rayon")]
+pub mod parallel;
+#[cfg(feature = "serde")]
+mod serde_support;
 
 #[derive(PartialEq)]
 pub enum PairPreferences {
     PreferredOver, Inconclusive, PreferredAgainst
 }
 
-#[derive(Default)]
-pub struct TrieNode {
-    children: HashMap<VoteValues, TrieNode>,
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(
+    serialize = "C: CandidateId + Serialize",
+    deserialize = "C: CandidateId + Deserialize<'de>"
+)))]
+pub struct GenericTrieNode<C> {
+    #[cfg_attr(feature = "serde", serde(with = "serde_support::children"))]
+    children: HashMap<GenericVoteValues<C>, GenericTrieNode<C>>,
     num_votes: u64
 }
 
-impl TrieNode {
+pub type TrieNode = GenericTrieNode<u32>;
+
+impl<C: CandidateId> Default for GenericTrieNode<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: CandidateId> GenericTrieNode<C> {
     pub fn new() -> Self {
-        TrieNode {
+        GenericTrieNode {
             children: HashMap::new(),
             num_votes: 0,
         }
@@ -34,41 +80,255 @@ impl TrieNode {
     }
 
     pub fn search_or_create_child(
-        &mut self, vote_value: VoteValues
-    ) -> &mut TrieNode {
+        &mut self, vote_value: GenericVoteValues<C>
+    ) -> &mut GenericTrieNode<C> {
         self.children.entry(vote_value).or_default()
     }
 
-    pub fn search_child(&self, vote_value: VoteValues) -> Option<&TrieNode> {
-        if let Some(node_ref) = self.children.get(&vote_value) {
-            Some(node_ref)
-        } else {
-            None
+    pub fn search_child(
+        &self, vote_value: GenericVoteValues<C>
+    ) -> Option<&GenericTrieNode<C>> {
+        self.children.get(&vote_value)
+    }
+
+    fn merge(&mut self, other: GenericTrieNode<C>) {
+        // adds up vote counts along paths shared by both tries
+        self.num_votes += other.num_votes;
+        for (vote_value, other_child) in other.children {
+            match self.children.entry(vote_value) {
+                Entry::Occupied(mut entry) => {
+                    entry.get_mut().merge(other_child);
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(other_child);
+                }
+            }
         }
     }
 }
 
-pub struct RankedChoiceVoteTrie {
-    root: TrieNode,
-    dowdall_score_map: HashMap<u32, f32>,
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(
+    try_from = "serde_support::TrieData<C>",
+    bound(
+        serialize = "C: CandidateId + Serialize",
+        deserialize = "C: CandidateId + Deserialize<'de>"
+    )
+))]
+pub struct GenericRankedChoiceVoteTrie<C> {
+    root: GenericTrieNode<C>,
+    // number of ballots ranking each candidate at each position
+    rank_counts: HashMap<C, Vec<u64>>,
     elimination_strategy: EliminationStrategies,
-    unique_candidates: HashSet<u32>
+    unique_candidates: HashSet<C>,
+    // candidates registered via declare_candidates, independent of ballots
+    declared_candidates: HashSet<C>,
+    // candidates (e.g. withdrawn ones) skipped over on every ballot
+    excluded_candidates: HashSet<C>
 }
 
-struct VoteTransfer<'a> {
-    next_candidate: u32,
-    next_node: &'a TrieNode,
-    num_votes: u64
+pub type RankedChoiceVoteTrie = GenericRankedChoiceVoteTrie<u32>;
+
+// snapshot of a single round of tabulation
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(
+    bound(deserialize = "C: CandidateId + Deserialize<'de>")
+))]
+pub struct ElectionRound<C> {
+    // number of votes held by each remaining candidate during the round
+    pub tallies: BTreeMap<C, u64>,
+    // candidates eliminated at the end of the round
+    pub eliminated: Vec<C>,
+    // why the candidates were eliminated (None if nobody was)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub elimination_reason: Option<EliminationReasons>,
+    // where the ballots of each eliminated candidate went
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub transfers: BTreeMap<C, VoteFlows<C>>,
+    // ballots not counted towards any candidate during the round
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub inactive_ballots: InactiveBallots
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum EliminationReasons {
+    // had the fewest votes (alone, or tied and all eliminated together)
+    FewestVotes,
+    // tied for the fewest votes, and had the lowest dowdall score
+    LowestDowdallScore,
+    // tied for the fewest votes, and lost head-to-head against the
+    // other tied candidates
+    LostRankedPairs,
+    // lost head-to-head among the candidates with the lowest and
+    // second-lowest number of votes
+    LostCondorcetRankedPairs,
+    // had fewer votes than the minimum vote threshold (RCTab rules)
+    BelowMinimumVotes,
+    // could not catch up with the next candidate even with the votes
+    // of every candidate below them (RCTab batch elimination)
+    BatchElimination,
+    // tied for the fewest votes, and lost the tie break (RCTab rules)
+    LostTiebreak
+}
+
+// running totals of ballots that no longer count for any candidate
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct InactiveBallots {
+    pub withhold_votes: u64,
+    pub abstain_votes: u64,
+    // ballots that rank no remaining candidates
+    pub exhausted_votes: u64
+}
+
+impl InactiveBallots {
+    pub fn total(&self) -> u64 {
+        self.withhold_votes + self.abstain_votes + self.exhausted_votes
+    }
+
+    pub(crate) fn add_flows<C>(&mut self, vote_flows: &VoteFlows<C>) {
+        self.withhold_votes += vote_flows.withhold_votes;
+        self.abstain_votes += vote_flows.abstain_votes;
+        self.exhausted_votes += vote_flows.exhausted_votes;
+    }
+}
+
+// where the ballots held by an eliminated candidate went
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(
+    bound(deserialize = "C: CandidateId + Deserialize<'de>")
+))]
+pub struct VoteFlows<C> {
+    // votes transferred to each remaining candidate
+    pub transfers: BTreeMap<C, u64>,
+    pub withhold_votes: u64,
+    pub abstain_votes: u64,
+    // votes on ballots that rank no further remaining candidates
+    pub exhausted_votes: u64
+}
+
+impl<C> VoteFlows<C> {
+    pub(crate) fn map_candidates<D: Ord, F>(&self, to_candidate: F) -> VoteFlows<D>
+    where F: Fn(&C) -> Option<D> {
+        // transfers to candidates that don't map to anything are left out
+        VoteFlows {
+            transfers: self.transfers.iter()
+                .filter_map(|(candidate, num_votes)| {
+                    Some((to_candidate(candidate)?, *num_votes))
+                }).collect(),
+            withhold_votes: self.withhold_votes,
+            abstain_votes: self.abstain_votes,
+            exhausted_votes: self.exhausted_votes
+        }
+    }
+}
+
+impl<C> Default for VoteFlows<C> {
+    fn default() -> Self {
+        VoteFlows {
+            transfers: BTreeMap::new(), withhold_votes: 0, abstain_votes: 0,
+            exhausted_votes: 0
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(
+    bound(deserialize = "C: CandidateId + Deserialize<'de>")
+))]
+pub struct ElectionResult<C> {
+    pub winner: Option<C>,
+    pub rounds: Vec<ElectionRound<C>>,
+    // candidates treated as eliminated before the first round
+    pub excluded_candidates: Vec<C>
 }
 
-struct VoteTransferChanges<'a> {
-    withhold_votes: u64, abstain_votes: u64,
-    // (next candidate, next node, num votes to transfer to next candidate)
-    vote_transfers: Vec<VoteTransfer<'a>>
+impl<C: Ord> ElectionRound<C> {
+    pub fn sorted_tallies(&self) -> Vec<(&C, u64)> {
+        // tallies with the most votes first, ties in candidate order
+        sort_by_votes(&self.tallies)
+    }
+}
+
+pub(crate) fn sort_by_votes<C: Ord>(tallies: &BTreeMap<C, u64>) -> Vec<(&C, u64)> {
+    let mut sorted_tallies: Vec<(&C, u64)> = tallies.iter()
+        .map(|(candidate, num_votes)| (candidate, *num_votes))
+        .collect();
+    sorted_tallies.sort_by(|(candidate1, votes1), (candidate2, votes2)| {
+        votes2.cmp(votes1).then_with(|| candidate1.cmp(candidate2))
+    });
+    sorted_tallies
+}
+
+impl<C> ElectionResult<C> {
+    pub(crate) fn map_candidates<D: Ord, F>(&self, to_candidate: F) -> ElectionResult<D>
+    where F: Fn(&C) -> Option<D> {
+        /*
+        candidates that don't map to anything are left out. The mapping
+        has to preserve candidate order, so that sorted lists of
+        candidates stay sorted
+        */
+        let to_candidates = |candidates: &[C]| -> Vec<D> {
+            candidates.iter().filter_map(&to_candidate).collect()
+        };
+        ElectionResult {
+            winner: self.winner.as_ref().and_then(&to_candidate),
+            rounds: self.rounds.iter().map(|election_round| ElectionRound {
+                tallies: election_round.tallies.iter()
+                    .filter_map(|(candidate, num_votes)| {
+                        Some((to_candidate(candidate)?, *num_votes))
+                    }).collect(),
+                eliminated: to_candidates(&election_round.eliminated),
+                elimination_reason: election_round.elimination_reason,
+                transfers: election_round.transfers.iter()
+                    .filter_map(|(candidate, vote_flows)| Some((
+                        to_candidate(candidate)?,
+                        vote_flows.map_candidates(&to_candidate)
+                    ))).collect(),
+                inactive_ballots: election_round.inactive_ballots
+            }).collect(),
+            excluded_candidates: to_candidates(&self.excluded_candidates)
+        }
+    }
+}
+
+impl<C: CandidateId> Extend<GenericRankedChoiceVoteTrie<C>>
+for GenericRankedChoiceVoteTrie<C> {
+    fn extend<T: IntoIterator<Item = GenericRankedChoiceVoteTrie<C>>>(
+        &mut self, tries: T
+    ) {
+        for trie in tries {
+            self.merge(trie);
+        }
+    }
+}
+
+impl<C: CandidateId> Sum for GenericRankedChoiceVoteTrie<C> {
+    fn sum<I: Iterator<Item = GenericRankedChoiceVoteTrie<C>>>(tries: I) -> Self {
+        let mut merged_trie = GenericRankedChoiceVoteTrie::new();
+        merged_trie.extend(tries);
+        merged_trie
+    }
+}
+
+// change in the election outcome when a candidate is removed from the race
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SpoilerEffect<C> {
+    pub spoiler: C,
+    pub original_winner: Option<C>,
+    // winner had the spoiler candidate never run
+    pub winner_without_spoiler: Option<C>
 }
 
 // strategies for how to eliminate candidates each round
-#[derive(Copy, Clone, PartialEq)]
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum EliminationStrategies {
     // removes all candidates with the lowest number of votes each round
     EliminateAll,
@@ -85,7 +345,7 @@ pub enum EliminationStrategies {
     CondorcetRankedPairs
 }
 
-fn is_graph_acyclic(graph: &DiGraph<u32, u64>) -> bool {
+fn is_graph_acyclic<N>(graph: &DiGraph<N, u64>) -> bool {
     /*
     checks if there doesn't exist any path of directed edges
     from some edge in the graph back to itself
@@ -94,9 +354,9 @@ fn is_graph_acyclic(graph: &DiGraph<u32, u64>) -> bool {
     let nodes: Vec<NodeIndex> = graph.node_indices().collect();
     let mut all_explored_nodes = HashSet::<NodeIndex>::new();
 
-    fn dfs_find_cycle(
+    fn dfs_find_cycle<N>(
         node: &NodeIndex, path: &mut Vec<NodeIndex>,
-        explored: &mut HashSet<NodeIndex>, graph: &DiGraph<u32, u64>
+        explored: &mut HashSet<NodeIndex>, graph: &DiGraph<N, u64>
     ) -> bool {
         // use DFS to see if a cycle can be created from paths starting from node
         explored.insert(*node);
@@ -134,7 +394,7 @@ fn is_graph_acyclic(graph: &DiGraph<u32, u64>) -> bool {
     true
 }
 
-fn is_graph_weakly_connected(graph: &DiGraph<u32, u64>) -> bool {
+fn is_graph_weakly_connected<N>(graph: &DiGraph<N, u64>) -> bool {
     /*
     checks if there is a path from every node to every other
     node when all the edges are converted from directed to undirected
@@ -173,19 +433,21 @@ fn is_graph_weakly_connected(graph: &DiGraph<u32, u64>) -> bool {
     explored_nodes.len() == graph.node_count()
 }
 
-impl Default for RankedChoiceVoteTrie {
+impl<C: CandidateId> Default for GenericRankedChoiceVoteTrie<C> {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl RankedChoiceVoteTrie {
+impl<C: CandidateId> GenericRankedChoiceVoteTrie<C> {
     pub fn new() -> Self {
-        RankedChoiceVoteTrie {
-            root: TrieNode::new(),
-            dowdall_score_map: Default::default(),
+        GenericRankedChoiceVoteTrie {
+            root: GenericTrieNode::new(),
+            rank_counts: Default::default(),
             elimination_strategy: EliminationStrategies::DowdallScoring,
             unique_candidates: Default::default(),
+            declared_candidates: Default::default(),
+            excluded_candidates: Default::default(),
         }
     }
 
@@ -193,484 +455,178 @@ impl RankedChoiceVoteTrie {
         self.root.get_num_votes()
     }
 
-    pub fn set_elimination_strategy(&mut self, strategy: EliminationStrategies) {
-        self.elimination_strategy = strategy;
+    pub fn declare_candidates(&mut self, candidates: &[C]) {
+        /*
+        registers candidates that should take part in the election
+        even if no ballot ranks them. Candidates without any first choice
+        votes start with a tally of zero and are eliminated first
+        */
+        for candidate in candidates {
+            self.unique_candidates.insert(candidate.clone());
+            self.declared_candidates.insert(candidate.clone());
+        }
     }
 
-    pub fn insert_votes(&mut self, votes: Vec<RankedVote>) {
+    pub fn merge(&mut self, other: GenericRankedChoiceVoteTrie<C>) {
+        /*
+        adds all the ballots counted in other into this trie (e.g. to
+        combine ballots counted separately in each precinct). Settings
+        (elimination strategy and excluded candidates) are kept as is
+        */
+        self.root.merge(other.root);
+        for (candidate, rank_counts) in other.rank_counts {
+            merge_rank_counts(
+                self.rank_counts.entry(candidate).or_default(), &rank_counts
+            );
+        }
+        self.unique_candidates.extend(other.unique_candidates);
+        self.declared_candidates.extend(other.declared_candidates);
+    }
+
+    pub fn insert_votes(&mut self, votes: Vec<GenericRankedVote<C>>) {
         for vote in votes {
             self.insert_vote(vote);
         }
     }
 
-    pub fn insert_vote(&mut self, vote: RankedVote) {
-        self.root.num_votes += 1;
+    pub fn insert_vote(&mut self, vote: GenericRankedVote<C>) {
+        self.insert_weighted_vote(vote, 1);
+    }
+
+    pub fn insert_weighted_vote(
+        &mut self, vote: GenericRankedVote<C>, num_votes: u64
+    ) {
+        // counts num_votes identical ballots in a single pass
+        // (ballots with no weight are left out, as tabulation expects
+        // every node in the trie to hold votes)
+        if num_votes == 0 { return }
+        self.root.num_votes += num_votes;
         let mut current = &mut self.root;
         let vote_items = vote.iter().enumerate();
 
         for (ranking, vote_value) in vote_items {
-            // println!("ITEM {}", ranking);
-            match vote_value {
-                VoteValues::SpecialVote(_) => {}
-                VoteValues::Candidate(candidate) => {
-                    self.unique_candidates.insert(candidate);
-                    let score = *self.dowdall_score_map
-                        .entry(candidate).or_insert(0f32);
-                    let new_score = score + 1.0 / (ranking + 1) as f32;
-                    assert!(new_score.is_finite());
-                    self.dowdall_score_map.insert(candidate, new_score);
+            match &vote_value {
+                GenericVoteValues::SpecialVote(_) => {}
+                GenericVoteValues::Candidate(candidate) => {
+                    self.unique_candidates.insert(candidate.clone());
+                    add_rank_count(
+                        self.rank_counts.entry(candidate.clone()).or_default(),
+                        ranking, num_votes
+                    );
                 }
             }
             let child = current.search_or_create_child(vote_value);
-            child.num_votes += 1;
+            child.num_votes += num_votes;
             current = child;
         };
     }
 
     pub fn search_nodes(
-        &mut self, ranked_vote: RankedVote
-    ) -> Option<Vec<&TrieNode>> {
+        &self, ranked_vote: GenericRankedVote<C>
+    ) -> Option<Vec<&GenericTrieNode<C>>> {
         // return path of trie nodes corresponding to ranked vote
         // returns None if there is no existing matching path in trie
         let mut current = &self.root;
-        let vote_values: Vec<VoteValues> = ranked_vote.iter().collect();
+        let vote_values: Vec<GenericVoteValues<C>> =
+            ranked_vote.iter().collect();
         let mut node_path = vec![current];
 
         for vote_value in vote_values {
-            let child_node = match current.children.get(&vote_value) {
-                Some(node) => node,
-                None => return None,
-            };
-
-            current = child_node;
+            current = current.children.get(&vote_value)?;
             node_path.push(current);
         }
         Some(node_path)
     }
 
-    fn transfer_next_votes<'a>(&'a self, node: &'a TrieNode) -> VoteTransferChanges {
-        let child_nodes = &node.children;
-        let mut transfer_changes = VoteTransferChanges {
-            withhold_votes: 0, abstain_votes: 0,
-            vote_transfers: Default::default(),
-        };
-
-        for (next_vote_value, next_node) in child_nodes {
-            match next_vote_value {
-                VoteValues::SpecialVote(special_vote) => {
-                    match special_vote {
-                        SpecialVotes::WITHHOLD => {
-                            transfer_changes.withhold_votes += 1;
-                        },
-                        SpecialVotes::ABSTAIN => {
-                            transfer_changes.abstain_votes += 1;
-                        }
-                    }
-                },
-                VoteValues::Candidate(next_candidate) => {
-                    transfer_changes.vote_transfers.push(VoteTransfer{
-                        next_candidate: *next_candidate, next_node,
-                        num_votes: next_node.num_votes
-                    });
-                }
-            }
-        }
-
-        transfer_changes
-    }
-
-    fn find_condorcet_ranked_pairs_weakest(
-        &self, candidate_vote_counts: &HashMap<u32, u64>,
-        ranked_pairs_map: &HashMap<(u32, u32), u64>,
-        lowest_vote_candidates: Vec<u32>
-    ) -> Vec<u32> {
-        println!("CC_PRE_RANK_FILTER {:?}", candidate_vote_counts);
-        println!("CC_PAIRS_MAP {:?}", ranked_pairs_map);
-        let mut vote_counts: Vec<u64> =
-            candidate_vote_counts.values().cloned().collect();
-        vote_counts.sort();
-
-        // get the second-lowest number of effective votes, or the lowest
-        // number of votes if the second-lowest number of effective votes
-        // is not available
-        let vote_threshold = match vote_counts.get(1) {
-            Some(second_lowest_votes) => { *second_lowest_votes }
-            None => {
-                match vote_counts.get(0) {
-                    Some(lowest_votes) => { *lowest_votes }
-                    None => { return vec![] }
-                }
-            }
+    pub fn run_election(
+        &self, votes: Vec<GenericRankedVote<C>>
+    ) -> Option<C> {
+        let mut rcv = GenericRankedChoiceVoteTrie {
+            root: Default::default(),
+            rank_counts: Default::default(),
+            elimination_strategy: self.elimination_strategy,
+            unique_candidates: self.declared_candidates.clone(),
+            declared_candidates: self.declared_candidates.clone(),
+            excluded_candidates: self.excluded_candidates.clone()
         };
-
-        // find candidates with less than or equal to the
-        // second-lowest number of effective votes
-        let mut weak_candidates: Vec<u32> = Vec::new();
-        for (candidate, num_votes) in candidate_vote_counts {
-            if *num_votes <= vote_threshold {
-                weak_candidates.push(*candidate);
-            }
-        }
-
-        let pairs_result = self.find_ranked_pairs_weakest(
-            weak_candidates, ranked_pairs_map
-        );
-
-        if pairs_result.1 == false {
-            lowest_vote_candidates
-        } else {
-            pairs_result.0
-        }
+        rcv.insert_votes(votes);
+        rcv.determine_winner()
     }
 
-    fn find_ranked_pairs_weakest(
-        &self, candidates: Vec<u32>,
-        ranked_pairs_map: &HashMap<(u32, u32), u64>
-    ) -> (Vec<u32>, bool) {
-        /*
-        Finds the candidates that perform the worst in pairwise
-        head-to-head comparison.
-        Returns the worst performing candidates, and whether it was possible
-        to construct a preference graph
-        */
-        let mut graph = DiGraph::<u32, u64>::new();
-        let mut node_map = HashMap::<u32, NodeIndex>::new();
-
+    pub fn rank_histogram(&self) -> BTreeMap<C, CandidateRankCounts> {
         /*
-        Determines whether candidate1 is preferred over candidate2 overall,
-        or vice versa, or there is no net preference between the two.
-        Also returns the net number of votes along said overall preference
+        returns how many ballots rank each candidate 1st, 2nd, 3rd and
+        so on, and how many ballots don't rank them at all. Ballots
+        are counted as cast (i.e. excluded candidates are not removed)
         */
-        let get_preference = |
-            candidate1: u32, candidate2: u32
-        | -> (PairPreferences, u64) {
-            let preferred_over_votes =
-                ranked_pairs_map.get(&(candidate1, candidate2))
-                .unwrap_or(&0);
-            let preferred_against_votes =
-                ranked_pairs_map.get(&(candidate2, candidate1))
-                .unwrap_or(&0);
-
-            /*
-            println!("C_PAIR {:?}", (
-                (candidate1, candidate2),
-                (preferred_over_votes, preferred_against_votes)
-            ));
-            */
-
-            match preferred_over_votes.cmp(preferred_against_votes) {
-                Ordering::Greater => {
-                    let strength =
-                        preferred_over_votes - preferred_against_votes;
-                    (PairPreferences::PreferredOver, strength)
-                }
-                Ordering::Equal => {
-                    (PairPreferences::Inconclusive, 0)
-                }
-                Ordering::Less => {
-                    let strength =
-                        preferred_against_votes - preferred_over_votes;
-                    (PairPreferences::PreferredAgainst, strength)
-                }
-            }
-        };
-
-        fn get_or_create_node (
-            graph: &mut DiGraph<u32, u64>,
-            node_map: &mut HashMap<u32, NodeIndex>,
-            candidate: u32
-        ) -> NodeIndex {
-            // println!("NODE_MAP_PRE {:?}", (candidate, &node_map, &graph));
-            let node = match node_map.get(&candidate) {
-                Some(node) => { *node }
-                None => {
-                    let node = graph.add_node(candidate);
-                    node_map.insert(candidate, node);
-                    node
-                }
-            };
-
-            // println!("NODE_MAP_POST {:?}", (candidate, &node_map, &graph));
-            node
-        }
-
-        // initialize all the nodes in the graph
-        for candidate in &candidates {
-            get_or_create_node(&mut graph, &mut node_map, *candidate);
-        }
-
-        // construct preference strength graph between candidates
-        for (candidate1, candidate2) in iproduct!(&candidates, &candidates) {
-            if candidate1 == candidate2 { continue }
-            let (preference, strength) =
-                get_preference(*candidate1, *candidate2);
-
-            match preference {
-                PairPreferences::PreferredAgainst => { continue }
-                PairPreferences::Inconclusive => { continue }
-                PairPreferences::PreferredOver => {}
-            }
-
-            assert!(preference == PairPreferences::PreferredOver);
-            let node1_idx =
-                get_or_create_node(&mut graph, &mut node_map, *candidate1);
-            let node2_idx =
-                get_or_create_node(&mut graph, &mut node_map, *candidate2);
-            if !graph.contains_edge(node1_idx, node2_idx) {
-                // println!("ADD_EDGE {:?}", (node1_idx, node2_idx));
-                graph.add_edge(node1_idx, node2_idx, strength);
-            }
-        }
-
-        // println!("GRAPH {:?}", graph);
-        // unable to establish pecking order among candidates
-        if !(is_graph_acyclic(&graph) && is_graph_weakly_connected(&graph)) {
-            /*
-            println!("POST_RANK_FILTER {:?}", (
-                &candidates, is_graph_acyclic(&graph),
-                is_graph_weakly_connected(&graph))
-            );
-            */
-            return (candidates.clone(), false);
-        }
-
-        let has_no_outgoing_edges = |&node: &NodeIndex| -> bool {
-            graph.neighbors_directed(node, Direction::Outgoing).count() == 0
-        };
-        let weakest_nodes: Vec<NodeIndex> = graph
-            .node_indices()
-            .filter(has_no_outgoing_edges)
-            .collect();
-
-        let weakest_candidates = weakest_nodes
-            .iter().map(|&index| graph[index]).collect();
-        // println!("POST_NODES {:?}", weakest_nodes);
-        // println!("POST_RANK_FILTER {:?}", weakest_candidates);
-        (weakest_candidates, true)
+        build_rank_histogram(
+            &self.rank_counts, &self.unique_candidates, self.get_num_votes()
+        )
     }
 
-    fn find_dowdall_weakest(&self, candidates: Vec<u32>) -> Vec<u32> {
+    pub fn positional_scores<F>(&self, weight: F) -> BTreeMap<C, BigRational>
+    where F: Fn(usize) -> BigRational {
         /*
-        returns the subset of candidates from the input candidates vector
-        that score the lowest according the dowdall scoring criteria
+        scores every candidate by summing weight(ranking) over all the
+        ballots that rank them, where ranking counts from 0 for first
+        choices (e.g. scoring::dowdall_weight). Scores are exact
         */
-        let mut min_score = f32::MAX;
-        let mut weakest_candidates: Vec<u32> = Vec::new();
-
-        for candidate in &candidates {
-            let score = self.dowdall_score_map.get(candidate)
-                .expect("score map should have scores for all candidates");
-            min_score = f32::min(*score, min_score);
-        }
-
-        for candidate in &candidates {
-            let score = self.dowdall_score_map.get(candidate)
-                .expect("score map should have scores for all candidates");
-            if f32::eq(score, &min_score) {
-                weakest_candidates.push(*candidate);
-            }
-        }
-
-        weakest_candidates
+        positional_scores(
+            &self.rank_counts, &self.unique_candidates, weight
+        )
     }
 
-    pub fn run_election(&self, votes: Vec<RankedVote>) -> Option<u32> {
-        let mut rcv = RankedChoiceVoteTrie {
-            root: Default::default(),
-            dowdall_score_map: Default::default(),
-            elimination_strategy: self.elimination_strategy.clone(),
-            unique_candidates: Default::default()
-        };
-        rcv.insert_votes(votes);
-        rcv.determine_winner()
+    pub fn dowdall_scores(&self) -> BTreeMap<C, BigRational> {
+        self.positional_scores(dowdall_weight)
     }
+}
 
-    fn build_ranked_pairs_map(
-        node: &TrieNode, search_path: &mut Vec<u32>,
-        ranked_pairs_map: &mut HashMap<(u32, u32), u64>,
-        unique_candidates: &HashSet<u32>
-    ) {
-        let kv_pairs_vec: Vec<(&VoteValues, &TrieNode)> =
-            node.children.iter().map(|(vote_value, node)| {
-                (vote_value, node)
-            }).collect();
-
-        // number of votes that terminate at node
-        let mut terminating_votes: u64 = node.num_votes;
-        // println!("NODE_VOTES {:?}", node.num_votes);
-
-        for (vote_value, child) in kv_pairs_vec {
-            // println!("CHILD_VOTES {:?}", child.num_votes);
-            assert!(terminating_votes >= child.num_votes);
-            terminating_votes -= child.num_votes;
-
-            let candidate = match vote_value {
-                VoteValues::SpecialVote(_) => { continue }
-                VoteValues::Candidate(candidate) => { candidate }
-            };
-
-            for preferable_candidate in search_path.iter() {
-                let ranked_pair = (*preferable_candidate, *candidate);
-                let pairwise_votes =
-                    ranked_pairs_map.entry(ranked_pair).or_insert(0);
-                *pairwise_votes += child.num_votes;
-            }
-
-            search_path.push(*candidate);
-            Self::build_ranked_pairs_map(
-                child, search_path, ranked_pairs_map, unique_candidates
-            );
-            search_path.pop();
-        };
-
-        if terminating_votes > 0 {
-            // println!("UNIQUE {:?}", unique_candidates);
-            // println!("TERMINATE {:?}", (&search_path, terminating_votes));
-            // candidates who weren't explicitly listed in current vote path
-            let search_path: &Vec<u32> = search_path;
-            let mut unspecified_candidates = unique_candidates.clone();
-            for candidate in search_path {
-                unspecified_candidates.remove(candidate);
-            }
-
-            let pairs = iproduct!(search_path, &unspecified_candidates);
-            for (preferable_candidate, candidate) in pairs {
-                let ranked_pair = (*preferable_candidate, *candidate);
-                let pairwise_votes =
-                    ranked_pairs_map.entry(ranked_pair).or_insert(0);
-                // println!("INSERT {:?}", (ranked_pair, terminating_votes));
-                *pairwise_votes += terminating_votes;
-            }
+impl<C: CandidateId> TabulationSource<C> for GenericRankedChoiceVoteTrie<C> {
+    type Key = C;
+    type Trie = Self;
+
+    fn tabulator(&self) -> Tabulator<'_, C, Self> {
+        Tabulator {
+            trie: self,
+            elimination_strategy: self.elimination_strategy,
+            unique_candidates: &self.unique_candidates,
+            excluded_candidates: &self.excluded_candidates,
+            rank_counts: Some(&self.rank_counts)
         }
     }
 
-    pub fn determine_winner(&self) -> Option<u32> {
-        // println!("RUN_ELECTION_START");
-        let mut candidate_vote_counts: HashMap<u32, u64> = HashMap::new();
-        let mut frontier_nodes:
-            HashMap<u32, Vec<&TrieNode>> = HashMap::new();
-        // total number of voters (who have no abstained from vote)
-        let mut effective_total_votes: u64 = 0;
-        // total number of votes that go to candidates
-        let mut total_candidate_votes: u64 = 0;
-
-        let kv_pairs_vec: Vec<(&VoteValues, &TrieNode)> =
-            self.root.children.iter().collect();
-
-        for (vote_value, node) in kv_pairs_vec {
-            match vote_value {
-                VoteValues::SpecialVote(SpecialVotes::ABSTAIN) => {}
-                VoteValues::SpecialVote(SpecialVotes::WITHHOLD) => {
-                    effective_total_votes += node.num_votes;
-                }
-                VoteValues::Candidate(candidate) => {
-                    candidate_vote_counts.insert(*candidate, node.num_votes);
-                    frontier_nodes.insert(*candidate, vec![node]);
-                    total_candidate_votes += node.num_votes;
-                    effective_total_votes += node.num_votes;
-                }
-            };
-        }
-
-        let mut ranked_pairs_map: HashMap<(u32, u32), u64> = HashMap::new();
-        let strategy = self.elimination_strategy;
-        if
-            (strategy == EliminationStrategies::RankedPairs) ||
-            (strategy == EliminationStrategies::CondorcetRankedPairs)
-        {
-            Self::build_ranked_pairs_map(
-                &self.root, &mut Vec::new(), &mut ranked_pairs_map,
-                &self.unique_candidates
-            );
-        }
-
-        while !candidate_vote_counts.is_empty() {
-            let mut min_candidate_votes: u64 = u64::MAX;
-            // impossible for any candidate to win as sum of
-            // candidate votes is under the total number of votes cast
-            if total_candidate_votes <= effective_total_votes / 2 {
-                return None;
-            }
-
-            for (candidate, num_votes) in &candidate_vote_counts {
-                min_candidate_votes = min(min_candidate_votes, *num_votes);
-                // some candidate has won a majority of the votes
-                if *num_votes > effective_total_votes / 2 {
-                    return Some(*candidate)
-                }
-            }
+    fn elimination_strategy_mut(&mut self) -> &mut EliminationStrategies {
+        &mut self.elimination_strategy
+    }
 
-            // find candidates with the lowest number of effective votes
-            let mut lowest_vote_candidates: Vec<u32> = Vec::new();
-            for (candidate, num_votes) in &candidate_vote_counts {
-                if *num_votes == min_candidate_votes {
-                    lowest_vote_candidates.push(*candidate);
-                }
-            }
+    fn excluded_candidates_mut(&mut self) -> &mut HashSet<C> {
+        &mut self.excluded_candidates
+    }
 
-            // further filter down candidates to eliminate using
-            // specified elimination strategy
-            let weakest_candidates = match self.elimination_strategy {
-                EliminationStrategies::EliminateAll => {
-                    lowest_vote_candidates
-                },
-                EliminationStrategies::DowdallScoring => {
-                    self.find_dowdall_weakest(lowest_vote_candidates)
-                },
-                EliminationStrategies::RankedPairs => {
-                    self.find_ranked_pairs_weakest(
-                        lowest_vote_candidates, &ranked_pairs_map
-                    ).0
-                },
-                EliminationStrategies::CondorcetRankedPairs => {
-                    self.find_condorcet_ranked_pairs_weakest(
-                        &candidate_vote_counts, &ranked_pairs_map,
-                        lowest_vote_candidates
-                    )
-                }
-            };
-
-            // find all candidates, nodes, and vote counts to transfer to
-            let mut all_vote_transfers: Vec<VoteTransfer> = Vec::new();
-            let mut new_withhold_votes: u64 = 0;
-            let mut new_abstain_votes: u64 = 0;
-
-            for weakest_candidate in weakest_candidates {
-                let candidate_nodes = frontier_nodes.get(&weakest_candidate)
-                    .expect("all uneliminated candidates must have node(s)");
-
-                for node in candidate_nodes {
-                    let transfer_result = self.transfer_next_votes(node);
-                    new_abstain_votes += transfer_result.abstain_votes;
-                    new_withhold_votes += transfer_result.withhold_votes;
-                    all_vote_transfers.extend(transfer_result.vote_transfers);
-                }
+    fn to_key(&self, candidate: &C) -> Option<C> {
+        Some(candidate.clone())
+    }
 
-                candidate_vote_counts.remove(&weakest_candidate);
-                frontier_nodes.remove(&weakest_candidate);
-            }
+    fn to_candidate(&self, candidate: &C) -> C {
+        candidate.clone()
+    }
+}
 
-            // 0 vote transfers will be done, election is unable to progress
-            if all_vote_transfers.is_empty() { return None; }
-            total_candidate_votes -= new_abstain_votes + new_withhold_votes;
-            effective_total_votes -= new_abstain_votes;
+impl<C: CandidateId> TrieTraversal<C> for GenericRankedChoiceVoteTrie<C> {
+    type Node<'a> = &'a GenericTrieNode<C> where C: 'a;
 
-            // conduct vote transfers to next candidates and trie nodes
-            for vote_transfer in all_vote_transfers {
-                let next_candidate = vote_transfer.next_candidate;
-                let vote_allocation = vote_transfer.num_votes;
-                assert!(vote_allocation > 0);
+    fn root_node(&self) -> &GenericTrieNode<C> {
+        &self.root
+    }
 
-                let next_candidate_votes = candidate_vote_counts
-                    .entry(next_candidate).or_insert(0);
-                let next_candidate_nodes = frontier_nodes
-                    .entry(next_candidate).or_default();
+    fn node_votes<'a>(&'a self, node: &'a GenericTrieNode<C>) -> u64 {
+        node.num_votes
+    }
 
-                *next_candidate_votes += vote_allocation;
-                next_candidate_nodes.push(vote_transfer.next_node);
-            }
+    fn visit_children<'a, F>(&'a self, node: &'a GenericTrieNode<C>, mut visit: F)
+    where F: FnMut(&GenericVoteValues<C>, &'a GenericTrieNode<C>) {
+        for (vote_value, child) in &node.children {
+            visit(vote_value, child);
         }
-
-        None
     }
 }
@@ -0,0 +1,285 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::process::ExitCode;
+use trie_rcv::{
+    ElectionResult, EliminationStrategies, RankedChoiceVoteTrie, TrieTabulation
+};
+use trie_rcv::formats::blt::read_blt;
+use trie_rcv::vote::RankedVote;
+
+/*
+command line tabulator: reads ballots from a file (or stdin), runs
+the election and prints the tallies of every round and the winner.
+The exit code tells a winner (0) apart from errors (1), ties (2) and
+elections where no candidate could win a majority (3)
+*/
+
+const USAGE: &str = "\
+usage: trie_rcv [--format FORMAT] [--strategy STRATEGY] [FILE]
+
+Tabulates a ranked choice election from FILE, or stdin if FILE is
+missing or '-'.
+
+options:
+  --format FORMAT      ints, blt or csv (default: from the file
+                       extension, otherwise ints)
+  --strategy STRATEGY  eliminate-all, dowdall, ranked-pairs or
+                       condorcet-ranked-pairs (default: dowdall)
+  -h, --help           print this message
+
+ints files have one ballot per line, listing candidate ids in order
+of preference separated by commas or spaces, where -1 withholds the
+vote and -2 abstains. Blank lines and lines starting with # are
+skipped. csv files are read as cast vote records with 'Choice ...'
+rank columns (needs the csv feature).
+
+exit codes: 0 winner, 1 error, 2 tie, 3 no majority";
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum InputFormat {
+    Ints,
+    Blt,
+    Csv
+}
+
+impl InputFormat {
+    fn parse(name: &str) -> Result<InputFormat, String> {
+        match name {
+            "ints" => Ok(InputFormat::Ints),
+            "blt" => Ok(InputFormat::Blt),
+            "csv" => Ok(InputFormat::Csv),
+            _ => Err(format!("unknown format {:?}", name))
+        }
+    }
+
+    fn from_path(path: &str) -> InputFormat {
+        let extension = path.rsplit_once('.')
+            .map(|(_, extension)| extension.to_ascii_lowercase());
+        match extension.as_deref() {
+            Some("blt") => InputFormat::Blt,
+            Some("csv") => InputFormat::Csv,
+            _ => InputFormat::Ints
+        }
+    }
+}
+
+fn parse_strategy(name: &str) -> Result<EliminationStrategies, String> {
+    match name {
+        "eliminate-all" => Ok(EliminationStrategies::EliminateAll),
+        "dowdall" => Ok(EliminationStrategies::DowdallScoring),
+        "ranked-pairs" => Ok(EliminationStrategies::RankedPairs),
+        "condorcet-ranked-pairs" => {
+            Ok(EliminationStrategies::CondorcetRankedPairs)
+        },
+        _ => Err(format!("unknown elimination strategy {:?}", name))
+    }
+}
+
+struct Options {
+    path: Option<String>,
+    format: Option<InputFormat>,
+    strategy: EliminationStrategies
+}
+
+fn parse_args(args: &[String]) -> Result<Option<Options>, String> {
+    // returns None if usage was asked for
+    let mut options = Options {
+        path: None, format: None,
+        strategy: EliminationStrategies::DowdallScoring
+    };
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut option_value = |option: &str| {
+            args.next().ok_or_else(|| format!("{} needs a value", option))
+        };
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "--format" => {
+                options.format = Some(InputFormat::parse(option_value(arg)?)?);
+            }
+            "--strategy" => {
+                options.strategy = parse_strategy(option_value(arg)?)?;
+            }
+            "-" => options.path = None,
+            option if option.starts_with('-') => {
+                return Err(format!("unknown option {:?}", option));
+            }
+            path => {
+                if options.path.is_some() {
+                    return Err("only one input file can be given".to_string());
+                }
+                options.path = Some(path.to_string());
+            }
+        }
+    }
+    Ok(Some(options))
+}
+
+// ballots to tabulate and the names of their candidates (if known)
+struct Election {
+    rcv: RankedChoiceVoteTrie,
+    candidate_names: Vec<String>
+}
+
+impl Election {
+    fn candidate_name(&self, candidate: u32) -> String {
+        // candidate n is named candidate_names[n - 1]
+        let index = (candidate as usize).checked_sub(1);
+        match index.and_then(|index| self.candidate_names.get(index)) {
+            Some(name) => name.clone(),
+            None => candidate.to_string()
+        }
+    }
+}
+
+fn read_ints<R: BufRead>(reader: R) -> Result<Election, String> {
+    let mut rcv = RankedChoiceVoteTrie::new();
+    for (index, line) in reader.lines().enumerate() {
+        let line = line.map_err(|err| err.to_string())?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') { continue }
+
+        let raw_vote = line.split(|character: char| {
+            character == ',' || character.is_whitespace()
+        }).filter(|token| !token.is_empty())
+            .map(|token| token.parse::<i32>().map_err(|_| {
+                format!("line {}: invalid candidate {:?}", index + 1, token)
+            }))
+            .collect::<Result<Vec<i32>, String>>()?;
+        let vote = RankedVote::from_vector(&raw_vote).map_err(|err| {
+            format!("line {}: {}", index + 1, err)
+        })?;
+        rcv.insert_vote(vote);
+    }
+    Ok(Election { rcv, candidate_names: Vec::new() })
+}
+
+#[cfg(feature = "csv")]
+fn read_csv<R: Read>(reader: R) -> Result<Election, String> {
+    use trie_rcv::formats::csv::{read_csv_ballots, CsvOptions};
+    use trie_rcv::registry::CandidateRegistry;
+
+    let mut registry = CandidateRegistry::new();
+    let mut rcv = RankedChoiceVoteTrie::new();
+    let log = read_csv_ballots(
+        reader, &CsvOptions::default(), &mut registry, &mut rcv
+    ).map_err(|err| err.to_string())?;
+    for row_error in &log.errors {
+        eprintln!("skipped line {}: {}", row_error.line, row_error.error);
+    }
+    Ok(Election { rcv, candidate_names: registry.names().to_vec() })
+}
+
+#[cfg(not(feature = "csv"))]
+fn read_csv<R: Read>(_reader: R) -> Result<Election, String> {
+    Err("reading CSV files needs the csv feature".to_string())
+}
+
+fn read_election(options: &Options) -> Result<Election, String> {
+    let reader: Box<dyn BufRead> = match &options.path {
+        Some(path) => {
+            let file = File::open(path).map_err(|err| {
+                format!("cannot open {}: {}", path, err)
+            })?;
+            Box::new(BufReader::new(file))
+        }
+        None => Box::new(BufReader::new(io::stdin()))
+    };
+    let format = options.format.unwrap_or_else(|| {
+        options.path.as_deref().map_or(InputFormat::Ints, InputFormat::from_path)
+    });
+
+    let mut election = match format {
+        InputFormat::Ints => read_ints(reader)?,
+        InputFormat::Blt => {
+            let blt_election = read_blt(reader).map_err(|err| err.to_string())?;
+            Election {
+                rcv: blt_election.to_trie(),
+                candidate_names: blt_election.candidate_names
+            }
+        }
+        InputFormat::Csv => read_csv(reader)?
+    };
+    election.rcv.set_elimination_strategy(options.strategy);
+    Ok(election)
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Outcome {
+    Winner,
+    Tie,
+    NoMajority
+}
+
+fn find_outcome(result: &ElectionResult<u32>, num_votes: u64) -> Outcome {
+    /*
+    the count stops without a winner either because every remaining
+    candidate is tied (and is eliminated together, or can't be told
+    apart), or because too many ballots were exhausted or withheld
+    for anyone to win a majority
+    */
+    if result.winner.is_some() { return Outcome::Winner }
+    let Some(last_round) = result.rounds.last() else {
+        return Outcome::NoMajority
+    };
+    if !last_round.eliminated.is_empty() { return Outcome::Tie }
+    let total_candidate_votes: u64 = last_round.tallies.values().sum();
+    match total_candidate_votes > num_votes / 2 {
+        true => Outcome::Tie,
+        false => Outcome::NoMajority
+    }
+}
+
+fn print_result(election: &Election, result: &ElectionResult<u32>) -> Outcome {
+    for (index, round) in result.rounds.iter().enumerate() {
+        println!("Round {}", index + 1);
+        for (candidate, num_votes) in round.sorted_tallies() {
+            println!("  {}: {}", election.candidate_name(*candidate), num_votes);
+        }
+        if !round.eliminated.is_empty() {
+            let eliminated: Vec<String> = round.eliminated.iter()
+                .map(|candidate| election.candidate_name(*candidate))
+                .collect();
+            println!("  eliminated: {}", eliminated.join(", "));
+        }
+    }
+
+    let outcome = find_outcome(result, election.rcv.get_num_votes());
+    match (outcome, &result.winner) {
+        (Outcome::Winner, Some(winner)) => {
+            println!("Winner: {}", election.candidate_name(*winner));
+        }
+        (Outcome::Tie, _) => println!("Result: tie"),
+        _ => println!("Result: no candidate won a majority")
+    }
+    outcome
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let options = match parse_args(&args) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(err) => {
+            eprintln!("trie_rcv: {}\n\n{}", err, USAGE);
+            return ExitCode::from(1);
+        }
+    };
+
+    let election = match read_election(&options) {
+        Ok(election) => election,
+        Err(err) => {
+            eprintln!("trie_rcv: {}", err);
+            return ExitCode::from(1);
+        }
+    };
+    let result = election.rcv.tabulate();
+    match print_result(&election, &result) {
+        Outcome::Winner => ExitCode::SUCCESS,
+        Outcome::Tie => ExitCode::from(2),
+        Outcome::NoMajority => ExitCode::from(3)
+    }
+}
@@ -0,0 +1,307 @@
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Write};
+#[cfg(feature = "mmap")]
+use std::fs::File;
+#[cfg(feature = "mmap")]
+use std::path::Path;
+#[cfg(feature = "mmap")]
+use memmap2::Mmap;
+use crate::{CandidateId, EliminationStrategies, GenericRankedChoiceVoteTrie};
+use crate::arena::GenericArenaVoteTrie;
+use crate::binary::{
+    read_candidates, read_varint, write_candidates, write_varint,
+    BinaryCandidate, BinaryFormatErrors
+};
+use crate::frozen::{
+    candidate_index, decode_key, FrozenNode, GenericFrozenTrie,
+    CANDIDATE_KEY_OFFSET
+};
+use crate::tabulation::{TabulationSource, Tabulator};
+use crate::traversal::TrieTraversal;
+use crate::vote::GenericVoteValues;
+
+const MAGIC_BYTES: &[u8; 4] = b"TRCM";
+// version 1 stored f32 dowdall scores instead of rank counts,
+// and is no longer read
+pub const MAPPED_FORMAT_VERSION: u8 = 2;
+
+/*
+every node is a fixed size little-endian record of
+(key: u32, subtree_len: u32, num_votes: u64), laid out depth-first
+the same way as in GenericFrozenTrie. The node table is aligned to
+NODE_TABLE_ALIGNMENT bytes from the start of the file
+*/
+const NODE_RECORD_LEN: usize = 16;
+const NODE_TABLE_ALIGNMENT: usize = 8;
+
+impl<C: CandidateId + BinaryCandidate> GenericFrozenTrie<C> {
+    pub fn write_mapped<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        /*
+        writes the trie in a format that can be tabulated in place
+        (e.g. memory-mapped) by GenericMappedTrie. The elimination
+        strategy and excluded candidates are not written
+        */
+        let mut header = Vec::new();
+        header.write_all(MAGIC_BYTES)?;
+        header.write_all(&[MAPPED_FORMAT_VERSION])?;
+        write_candidates(&mut header, self.candidates.iter())?;
+
+        let mut rank_counts: Vec<(&u32, &Vec<u64>)> =
+            self.rank_counts.iter().collect();
+        rank_counts.sort_by_key(|(index, _)| **index);
+        write_varint(&mut header, rank_counts.len() as u64)?;
+        for (index, candidate_rank_counts) in rank_counts {
+            write_varint(&mut header, u64::from(*index))?;
+            write_varint(&mut header, candidate_rank_counts.len() as u64)?;
+            for num_votes in candidate_rank_counts {
+                write_varint(&mut header, *num_votes)?;
+            }
+        }
+
+        write_varint(&mut header, self.nodes.len() as u64)?;
+        let padding = header.len().next_multiple_of(NODE_TABLE_ALIGNMENT)
+            - header.len();
+        header.resize(header.len() + padding, 0);
+        writer.write_all(&header)?;
+
+        for node in &self.nodes {
+            writer.write_all(&node.key.to_le_bytes())?;
+            writer.write_all(&node.subtree_len.to_le_bytes())?;
+            writer.write_all(&node.num_votes.to_le_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+impl<C: CandidateId + BinaryCandidate> GenericRankedChoiceVoteTrie<C> {
+    pub fn write_mapped<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.freeze().write_mapped(writer)
+    }
+}
+
+impl<C: CandidateId + BinaryCandidate> GenericArenaVoteTrie<C> {
+    pub fn write_mapped<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.freeze().write_mapped(writer)
+    }
+}
+
+pub struct GenericMappedTrie<C, B> {
+    /*
+    trie tabulated directly from the bytes written by write_mapped
+    (e.g. a memory-mapped file), without loading its nodes onto the
+    heap. Only the candidate table and rank counts are parsed
+    up front
+    */
+    bytes: B,
+    node_table_offset: usize,
+    num_nodes: u32,
+    candidates: Vec<C>,
+    rank_counts: HashMap<u32, Vec<u64>>,
+    elimination_strategy: EliminationStrategies,
+    unique_candidates: HashSet<u32>,
+    excluded_candidates: HashSet<u32>
+}
+
+pub type MappedTrie<B> = GenericMappedTrie<u32, B>;
+
+fn read_node(bytes: &[u8], node_table_offset: usize, index: u32) -> FrozenNode {
+    let start = node_table_offset + index as usize * NODE_RECORD_LEN;
+    let record = &bytes[start..start + NODE_RECORD_LEN];
+    FrozenNode {
+        key: u32::from_le_bytes(record[0..4].try_into().unwrap()),
+        subtree_len: u32::from_le_bytes(record[4..8].try_into().unwrap()),
+        num_votes: u64::from_le_bytes(record[8..16].try_into().unwrap())
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl<C: CandidateId + BinaryCandidate> GenericMappedTrie<C, Mmap> {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, BinaryFormatErrors> {
+        let file = File::open(path)?;
+        // SAFETY: the file must not be modified while it is mapped,
+        // as the node table is read in place
+        let mmap = unsafe { Mmap::map(&file)? };
+        Self::from_bytes(mmap)
+    }
+}
+
+impl<C: CandidateId + BinaryCandidate, B: AsRef<[u8]>> GenericMappedTrie<C, B> {
+    pub fn from_bytes(bytes: B) -> Result<Self, BinaryFormatErrors> {
+        let data = bytes.as_ref();
+        let mut reader = data;
+        if !reader.starts_with(MAGIC_BYTES) {
+            return Err(BinaryFormatErrors::InvalidMagicBytes);
+        }
+        reader = &reader[MAGIC_BYTES.len()..];
+        let version = *reader.first()
+            .ok_or(BinaryFormatErrors::InvalidNodeTable)?;
+        if version != MAPPED_FORMAT_VERSION {
+            return Err(BinaryFormatErrors::UnsupportedVersion(version));
+        }
+        reader = &reader[1..];
+
+        let candidates: Vec<C> = read_candidates(&mut reader)?;
+        let mut rank_counts: HashMap<u32, Vec<u64>> = HashMap::new();
+        let num_candidate_scores = read_varint(&mut reader)?;
+        for _ in 0..num_candidate_scores {
+            let index = read_varint(&mut reader)?;
+            let index = u32::try_from(index).ok()
+                .filter(|&index| (index as usize) < candidates.len())
+                .ok_or(BinaryFormatErrors::InvalidCandidateIndex(index))?;
+            let num_rankings = read_varint(&mut reader)?;
+            let mut candidate_rank_counts = Vec::new();
+            for _ in 0..num_rankings {
+                candidate_rank_counts.push(read_varint(&mut reader)?);
+            }
+            rank_counts.insert(index, candidate_rank_counts);
+        }
+
+        let num_nodes = u32::try_from(read_varint(&mut reader)?)
+            .map_err(|_| BinaryFormatErrors::InvalidNodeTable)?;
+        let header_len = data.len() - reader.len();
+        let node_table_offset = header_len.next_multiple_of(NODE_TABLE_ALIGNMENT);
+        let node_table_end = (num_nodes as usize).checked_mul(NODE_RECORD_LEN)
+            .and_then(|table_len| table_len.checked_add(node_table_offset))
+            .ok_or(BinaryFormatErrors::InvalidNodeTable)?;
+        if num_nodes == 0 || node_table_end > data.len() {
+            return Err(BinaryFormatErrors::InvalidNodeTable);
+        }
+
+        validate_nodes(data, node_table_offset, num_nodes, candidates.len())?;
+        Ok(GenericMappedTrie {
+            unique_candidates: (0..candidates.len() as u32).collect(),
+            bytes, node_table_offset, num_nodes, candidates, rank_counts,
+            elimination_strategy: EliminationStrategies::DowdallScoring,
+            excluded_candidates: HashSet::new()
+        })
+    }
+}
+
+fn validate_nodes(
+    bytes: &[u8], node_table_offset: usize, num_nodes: u32,
+    num_candidates: usize
+) -> Result<(), BinaryFormatErrors> {
+    /*
+    checks that every subtree lies within its parent's subtree, and
+    that children hold at least one vote but never more than their
+    parent, so that traversing the trie can't run past the end of
+    the node table and tabulation never sees an empty node
+    */
+    // (end of subtree, votes at subtree root, votes of its children)
+    let mut open_subtrees: Vec<(u32, u64, u64)> = Vec::new();
+
+    for index in 0..num_nodes {
+        while open_subtrees.last().is_some_and(|(end, _, _)| *end <= index) {
+            open_subtrees.pop();
+        }
+
+        let node = read_node(bytes, node_table_offset, index);
+        let subtree_end = index.checked_add(node.subtree_len)
+            .filter(|_| node.subtree_len > 0)
+            .ok_or(BinaryFormatErrors::InvalidNodeTable)?;
+
+        match open_subtrees.last_mut() {
+            None => {
+                // the root's subtree has to span the whole table
+                if index != 0 || subtree_end != num_nodes {
+                    return Err(BinaryFormatErrors::InvalidNodeTable);
+                }
+            }
+            Some((parent_end, parent_votes, child_votes)) => {
+                if subtree_end > *parent_end {
+                    return Err(BinaryFormatErrors::InvalidNodeTable);
+                }
+                *child_votes = child_votes.checked_add(node.num_votes)
+                    .filter(|_| node.num_votes > 0)
+                    .filter(|child_votes| child_votes <= parent_votes)
+                    .ok_or(BinaryFormatErrors::InconsistentVoteCounts)?;
+                if node.key >= CANDIDATE_KEY_OFFSET {
+                    let candidate_index = node.key - CANDIDATE_KEY_OFFSET;
+                    if candidate_index as usize >= num_candidates {
+                        return Err(BinaryFormatErrors::InvalidCandidateIndex(
+                            u64::from(candidate_index)
+                        ));
+                    }
+                }
+            }
+        }
+        open_subtrees.push((subtree_end, node.num_votes, 0));
+    }
+    Ok(())
+}
+
+impl<C: CandidateId, B: AsRef<[u8]>> GenericMappedTrie<C, B> {
+    pub fn get_num_votes(&self) -> u64 {
+        self.node(0).num_votes
+    }
+
+    pub fn get_num_nodes(&self) -> usize {
+        self.num_nodes as usize
+    }
+
+    pub fn candidates(&self) -> &[C] {
+        &self.candidates
+    }
+
+    fn node(&self, index: u32) -> FrozenNode {
+        read_node(self.bytes.as_ref(), self.node_table_offset, index)
+    }
+}
+
+impl<C: CandidateId + BinaryCandidate, B: AsRef<[u8]>> TabulationSource<C>
+for GenericMappedTrie<C, B> {
+    type Key = u32;
+    type Trie = Self;
+
+    fn tabulator(&self) -> Tabulator<'_, u32, Self> {
+        Tabulator {
+            trie: self,
+            elimination_strategy: self.elimination_strategy,
+            unique_candidates: &self.unique_candidates,
+            excluded_candidates: &self.excluded_candidates,
+            rank_counts: Some(&self.rank_counts)
+        }
+    }
+
+    fn elimination_strategy_mut(&mut self) -> &mut EliminationStrategies {
+        &mut self.elimination_strategy
+    }
+
+    fn excluded_candidates_mut(&mut self) -> &mut HashSet<u32> {
+        &mut self.excluded_candidates
+    }
+
+    fn to_key(&self, candidate: &C) -> Option<u32> {
+        candidate_index(&self.candidates, candidate)
+    }
+
+    fn to_candidate(&self, index: &u32) -> C {
+        self.candidates[*index as usize].clone()
+    }
+}
+
+impl<C, B: AsRef<[u8]>> TrieTraversal<u32> for GenericMappedTrie<C, B> {
+    type Node<'a> = u32 where Self: 'a;
+
+    fn root_node(&self) -> u32 {
+        0
+    }
+
+    fn node_votes(&self, node: u32) -> u64 {
+        read_node(self.bytes.as_ref(), self.node_table_offset, node).num_votes
+    }
+
+    fn visit_children<'a, F>(&'a self, node: u32, mut visit: F)
+    where F: FnMut(&GenericVoteValues<u32>, Self::Node<'a>) {
+        let bytes = self.bytes.as_ref();
+        let subtree_len =
+            read_node(bytes, self.node_table_offset, node).subtree_len;
+        let subtree_end = node + subtree_len;
+        let mut child = node + 1;
+        while child < subtree_end {
+            let child_node = read_node(bytes, self.node_table_offset, child);
+            visit(&decode_key(child_node.key), child);
+            child += child_node.subtree_len;
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use std::collections::{HashMap, HashSet};
+use rayon::prelude::*;
+use crate::{CandidateId, ElectionResult, GenericRankedChoiceVoteTrie};
+use crate::tabulation::{
+    build_child_ranked_pairs_map, RankedPairsMap, TabulationSource
+};
+use crate::vote::GenericRankedVote;
+
+fn sum_ranked_pairs_maps<C: CandidateId>(
+    mut ranked_pairs_map: RankedPairsMap<C>, other: RankedPairsMap<C>
+) -> RankedPairsMap<C> {
+    for (ranked_pair, pairwise_votes) in other {
+        *ranked_pairs_map.entry(ranked_pair).or_insert(0) += pairwise_votes;
+    }
+    ranked_pairs_map
+}
+
+impl<C: CandidateId + Send + Sync> GenericRankedChoiceVoteTrie<C> {
+    pub fn par_insert_votes(&mut self, votes: Vec<GenericRankedVote<C>>) {
+        /*
+        inserts votes using all available threads: each thread builds
+        a sub-trie from its share of the votes, and the sub-tries are
+        then merged together into this trie
+        */
+        let sub_trie = votes.into_par_iter()
+            .fold(GenericRankedChoiceVoteTrie::new, |mut trie, vote| {
+                trie.insert_vote(vote);
+                trie
+            })
+            .reduce(GenericRankedChoiceVoteTrie::new, |mut trie, other| {
+                trie.merge(other);
+                trie
+            });
+        self.merge(sub_trie);
+    }
+
+    fn par_build_ranked_pairs_map(
+        &self, excluded_candidates: &HashSet<C>
+    ) -> RankedPairsMap<C> {
+        // each subtree under the root is independent of the others,
+        // so their pairwise counts can be built separately and summed
+        let contesting_candidates = self.tabulator()
+            .contesting_candidates(excluded_candidates);
+        self.root.children.par_iter()
+            .map(|(vote_value, child)| {
+                let mut ranked_pairs_map = HashMap::new();
+                build_child_ranked_pairs_map(
+                    self, vote_value, child, &mut Vec::new(),
+                    &mut ranked_pairs_map, &contesting_candidates,
+                    excluded_candidates
+                );
+                ranked_pairs_map
+            })
+            .reduce(HashMap::new, sum_ranked_pairs_maps)
+    }
+
+    pub fn par_tabulate(&self) -> ElectionResult<C> {
+        // same as tabulate, but builds the pairwise map in parallel
+        let tabulator = self.tabulator();
+        let excluded_candidates = &self.excluded_candidates;
+        let ranked_pairs_map = match tabulator.uses_ranked_pairs() {
+            true => self.par_build_ranked_pairs_map(excluded_candidates),
+            false => HashMap::new()
+        };
+        tabulator.tabulate_with_ranked_pairs(excluded_candidates, ranked_pairs_map)
+    }
+
+    pub fn par_determine_winner(&self) -> Option<C> {
+        self.par_tabulate().winner
+    }
+}
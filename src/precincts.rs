@@ -0,0 +1,218 @@
+use std::cell::OnceCell;
+use crate::{
+    CandidateId, ElectionResult, ElectionRound, EliminationStrategies,
+    GenericRankedChoiceVoteTrie, TrieTabulation
+};
+use crate::vote::GenericRankedVote;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub struct JurisdictionId(usize);
+
+struct Jurisdiction<C> {
+    name: String,
+    parent: Option<JurisdictionId>,
+    children: Vec<JurisdictionId>,
+    // ballots counted directly in this jurisdiction (i.e. at a precinct)
+    ballots: GenericRankedChoiceVoteTrie<C>,
+    // ballots of this jurisdiction and all jurisdictions under it,
+    // merged on demand and cleared whenever any of those ballots change
+    aggregate: OnceCell<GenericRankedChoiceVoteTrie<C>>
+}
+
+// breakdown of the global tabulation for the ballots of one jurisdiction
+#[derive(Clone, Debug, PartialEq)]
+pub struct JurisdictionReport<C> {
+    pub jurisdiction: JurisdictionId,
+    pub name: String,
+    pub num_votes: u64,
+    // rounds of the global tabulation, tallied using only this
+    // jurisdiction's ballots
+    pub result: ElectionResult<C>
+}
+
+impl<C> JurisdictionReport<C> {
+    pub fn first_round(&self) -> Option<&ElectionRound<C>> {
+        self.result.rounds.first()
+    }
+
+    pub fn final_round(&self) -> Option<&ElectionRound<C>> {
+        self.result.rounds.last()
+    }
+}
+
+pub struct PrecinctTree<C> {
+    /*
+    hierarchy of jurisdictions (e.g. district > ward > precinct), with
+    ballots counted at each precinct. The election is tabulated across
+    all ballots, and each jurisdiction's ballots can then be broken down
+    according to the rounds of that global tabulation
+    */
+    jurisdictions: Vec<Jurisdiction<C>>,
+    elimination_strategy: EliminationStrategies,
+    declared_candidates: Vec<C>,
+    excluded_candidates: Vec<C>
+}
+
+impl<C: CandidateId> PrecinctTree<C> {
+    pub fn new(root_name: &str) -> Self {
+        let mut precinct_tree = PrecinctTree {
+            jurisdictions: Vec::new(),
+            elimination_strategy: EliminationStrategies::DowdallScoring,
+            declared_candidates: Vec::new(),
+            excluded_candidates: Vec::new()
+        };
+        precinct_tree.create_jurisdiction(root_name, None);
+        precinct_tree
+    }
+
+    pub fn root(&self) -> JurisdictionId {
+        JurisdictionId(0)
+    }
+
+    pub fn add_jurisdiction(
+        &mut self, parent: JurisdictionId, name: &str
+    ) -> JurisdictionId {
+        let jurisdiction = self.create_jurisdiction(name, Some(parent));
+        self.jurisdictions[parent.0].children.push(jurisdiction);
+        jurisdiction
+    }
+
+    fn create_jurisdiction(
+        &mut self, name: &str, parent: Option<JurisdictionId>
+    ) -> JurisdictionId {
+        let jurisdiction = JurisdictionId(self.jurisdictions.len());
+        self.jurisdictions.push(Jurisdiction {
+            name: name.to_string(),
+            parent,
+            children: Vec::new(),
+            ballots: self.new_trie(),
+            aggregate: OnceCell::new()
+        });
+        self.invalidate(jurisdiction);
+        jurisdiction
+    }
+
+    pub fn get_name(&self, jurisdiction: JurisdictionId) -> &str {
+        &self.jurisdictions[jurisdiction.0].name
+    }
+
+    pub fn get_parent(
+        &self, jurisdiction: JurisdictionId
+    ) -> Option<JurisdictionId> {
+        self.jurisdictions[jurisdiction.0].parent
+    }
+
+    pub fn get_children(&self, jurisdiction: JurisdictionId) -> &[JurisdictionId] {
+        &self.jurisdictions[jurisdiction.0].children
+    }
+
+    pub fn jurisdictions(&self) -> impl Iterator<Item = JurisdictionId> {
+        (0..self.jurisdictions.len()).map(JurisdictionId)
+    }
+
+    pub fn set_elimination_strategy(&mut self, strategy: EliminationStrategies) {
+        self.elimination_strategy = strategy;
+        self.invalidate_all();
+    }
+
+    pub fn declare_candidates(&mut self, candidates: &[C]) {
+        self.declared_candidates.extend(candidates.iter().cloned());
+        self.invalidate_all();
+    }
+
+    pub fn exclude_candidates(&mut self, candidates: &[C]) {
+        self.excluded_candidates.extend(candidates.iter().cloned());
+        self.invalidate_all();
+    }
+
+    pub fn insert_vote(
+        &mut self, jurisdiction: JurisdictionId, vote: GenericRankedVote<C>
+    ) {
+        self.jurisdictions[jurisdiction.0].ballots.insert_vote(vote);
+        self.invalidate(jurisdiction);
+    }
+
+    pub fn insert_votes(
+        &mut self, jurisdiction: JurisdictionId,
+        votes: Vec<GenericRankedVote<C>>
+    ) {
+        self.jurisdictions[jurisdiction.0].ballots.insert_votes(votes);
+        self.invalidate(jurisdiction);
+    }
+
+    pub fn merge_trie(
+        &mut self, jurisdiction: JurisdictionId,
+        trie: GenericRankedChoiceVoteTrie<C>
+    ) {
+        self.jurisdictions[jurisdiction.0].ballots.merge(trie);
+        self.invalidate(jurisdiction);
+    }
+
+    fn new_trie(&self) -> GenericRankedChoiceVoteTrie<C> {
+        let mut trie = GenericRankedChoiceVoteTrie::new();
+        trie.set_elimination_strategy(self.elimination_strategy);
+        trie.declare_candidates(&self.declared_candidates);
+        trie.exclude_candidates(&self.excluded_candidates);
+        trie
+    }
+
+    fn invalidate(&mut self, jurisdiction: JurisdictionId) {
+        // clears cached aggregates of the jurisdiction and its ancestors
+        let mut current = Some(jurisdiction);
+        while let Some(jurisdiction) = current {
+            self.jurisdictions[jurisdiction.0].aggregate.take();
+            current = self.jurisdictions[jurisdiction.0].parent;
+        }
+    }
+
+    fn invalidate_all(&mut self) {
+        for jurisdiction in &mut self.jurisdictions {
+            jurisdiction.aggregate.take();
+        }
+    }
+
+    pub fn aggregate(
+        &self, jurisdiction: JurisdictionId
+    ) -> &GenericRankedChoiceVoteTrie<C> {
+        /*
+        returns a trie with all ballots counted in the jurisdiction
+        and its sub-jurisdictions, merging them on first use
+        */
+        let entry = &self.jurisdictions[jurisdiction.0];
+        entry.aggregate.get_or_init(|| {
+            let mut trie = self.new_trie();
+            trie.merge(entry.ballots.clone());
+            for child in &entry.children {
+                trie.merge(self.aggregate(*child).clone());
+            }
+            trie
+        })
+    }
+
+    pub fn tabulate(&self) -> ElectionResult<C> {
+        self.aggregate(self.root()).tabulate()
+    }
+
+    pub fn report(&self, jurisdiction: JurisdictionId) -> JurisdictionReport<C> {
+        self.report_for_result(jurisdiction, &self.tabulate())
+    }
+
+    pub fn reports(&self) -> Vec<JurisdictionReport<C>> {
+        let election_result = self.tabulate();
+        self.jurisdictions().map(|jurisdiction| {
+            self.report_for_result(jurisdiction, &election_result)
+        }).collect()
+    }
+
+    fn report_for_result(
+        &self, jurisdiction: JurisdictionId, election_result: &ElectionResult<C>
+    ) -> JurisdictionReport<C> {
+        let trie = self.aggregate(jurisdiction);
+        JurisdictionReport {
+            jurisdiction,
+            name: self.get_name(jurisdiction).to_string(),
+            num_votes: trie.get_num_votes(),
+            result: trie.replay_tabulation(election_result)
+        }
+    }
+}
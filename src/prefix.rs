@@ -0,0 +1,137 @@
+use std::collections::BTreeMap;
+use crate::{CandidateId, GenericRankedChoiceVoteTrie, GenericTrieNode};
+use crate::arena::{ArenaNode, GenericArenaVoteTrie};
+use crate::traversal::TrieTraversal;
+use crate::vote::{GenericRankedVote, GenericVoteValues};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum PrefixPattern<C> {
+    // matches any ranked candidate (but not a withheld or abstained vote)
+    AnyCandidate,
+    Candidate(C)
+}
+
+impl<C: PartialEq> PrefixPattern<C> {
+    fn matches(&self, vote_value: &GenericVoteValues<C>) -> bool {
+        match (self, vote_value) {
+            (_, GenericVoteValues::SpecialVote(_)) => false,
+            (PrefixPattern::AnyCandidate, _) => true,
+            (
+                PrefixPattern::Candidate(candidate),
+                GenericVoteValues::Candidate(vote_candidate)
+            ) => candidate == vote_candidate
+        }
+    }
+}
+
+impl<C: CandidateId> GenericRankedChoiceVoteTrie<C> {
+    pub fn count_prefix(&self, prefix: &[C]) -> u64 {
+        // number of ballots whose top choices are exactly prefix, in order
+        self.prefix_node(prefix).map_or(0, |node| node.get_num_votes())
+    }
+
+    pub fn next_choices(
+        &self, prefix: &[C]
+    ) -> BTreeMap<GenericVoteValues<C>, u64> {
+        /*
+        returns how many ballots starting with prefix go on to rank
+        each candidate (or special vote) next. Ballots that end right
+        after prefix are not listed, i.e. there are
+        count_prefix(prefix) - (sum of next choices) of them
+        */
+        count_next_choices(self, self.prefix_node(prefix))
+    }
+
+    fn prefix_node(&self, prefix: &[C]) -> Option<&GenericTrieNode<C>> {
+        // every ballot starts with the empty prefix, which isn't a
+        // valid vote, and no ballot ranks a candidate twice
+        if prefix.is_empty() { return Some(self.root_node()) }
+        let prefix_vote = GenericRankedVote::new(prefix.to_vec(), None).ok()?;
+        self.search_nodes(prefix_vote)?.pop()
+    }
+
+    pub fn count_matching(&self, pattern: &[PrefixPattern<C>]) -> u64 {
+        // e.g. [AnyCandidate, Candidate(7)] counts ballots ranking 7 second
+        count_matching(self, pattern)
+    }
+
+    pub fn next_choices_matching(
+        &self, pattern: &[PrefixPattern<C>]
+    ) -> BTreeMap<GenericVoteValues<C>, u64> {
+        next_choices_matching(self, pattern)
+    }
+}
+
+impl<C: CandidateId> GenericArenaVoteTrie<C> {
+    pub fn count_prefix(&self, prefix: &[C]) -> u64 {
+        self.prefix_node(prefix).map_or(0, |node| node.get_num_votes())
+    }
+
+    pub fn next_choices(
+        &self, prefix: &[C]
+    ) -> BTreeMap<GenericVoteValues<C>, u64> {
+        count_next_choices(self, self.prefix_node(prefix))
+    }
+
+    fn prefix_node(&self, prefix: &[C]) -> Option<&ArenaNode<C>> {
+        if prefix.is_empty() { return Some(self.root_node()) }
+        let prefix_vote = GenericRankedVote::new(prefix.to_vec(), None).ok()?;
+        self.search_nodes(prefix_vote)?.pop()
+    }
+
+    pub fn count_matching(&self, pattern: &[PrefixPattern<C>]) -> u64 {
+        count_matching(self, pattern)
+    }
+
+    pub fn next_choices_matching(
+        &self, pattern: &[PrefixPattern<C>]
+    ) -> BTreeMap<GenericVoteValues<C>, u64> {
+        next_choices_matching(self, pattern)
+    }
+}
+
+fn count_matching<C: CandidateId, T: TrieTraversal<C>>(
+    trie: &T, pattern: &[PrefixPattern<C>]
+) -> u64 {
+    matching_nodes(trie, pattern).into_iter()
+        .map(|node| trie.node_votes(node))
+        .sum()
+}
+
+fn next_choices_matching<C: CandidateId, T: TrieTraversal<C>>(
+    trie: &T, pattern: &[PrefixPattern<C>]
+) -> BTreeMap<GenericVoteValues<C>, u64> {
+    count_next_choices(trie, matching_nodes(trie, pattern))
+}
+
+fn count_next_choices<'a, C, T, I>(
+    trie: &'a T, nodes: I
+) -> BTreeMap<GenericVoteValues<C>, u64>
+where C: CandidateId, T: TrieTraversal<C>, I: IntoIterator<Item = T::Node<'a>> {
+    // votes going on to each vote value right after any of nodes
+    let mut next_choices = BTreeMap::new();
+    for node in nodes {
+        trie.visit_children(node, |vote_value, child| {
+            *next_choices.entry(vote_value.clone()).or_insert(0) +=
+                trie.node_votes(child);
+        });
+    }
+    next_choices
+}
+
+fn matching_nodes<'a, C: CandidateId, T: TrieTraversal<C>>(
+    trie: &'a T, pattern: &[PrefixPattern<C>]
+) -> Vec<T::Node<'a>> {
+    // trie nodes at the end of every ballot prefix matching pattern
+    let mut nodes = vec![trie.root_node()];
+    for pattern_value in pattern {
+        let mut matches = Vec::new();
+        for node in nodes {
+            trie.visit_children(node, |vote_value, child| {
+                if pattern_value.matches(vote_value) { matches.push(child) }
+            });
+        }
+        nodes = matches;
+    }
+    nodes
+}
@@ -0,0 +1,728 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write;
+use crate::{
+    sort_by_votes, CandidateId, ElectionResult, ElectionRound,
+    EliminationReasons, GenericRankedChoiceVoteTrie, InactiveBallots, VoteFlows
+};
+use crate::arena::GenericArenaVoteTrie;
+use crate::json::JsonValue;
+use crate::tabulation::TabulationState;
+use crate::traversal::TrieTraversal;
+use crate::vote::{GenericRankedVote, SpecialVotes};
+
+/*
+tabulation following the rule set of RCTab, the Ranked Choice Voting
+Resource Center's tabulator, so that results can be diffed against
+it. Unlike the tabulation in lib.rs, a candidate wins with a majority
+of the continuing ballots (i.e. inactive ballots, including withheld
+votes, no longer count towards the threshold), and exactly one
+candidate is eliminated per round unless batch elimination applies.
+Overvote, skipped rank and duplicate rules are applied when ballots
+are read (see BallotRules), since ranked votes can't hold overvotes
+*/
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum OvervoteRule {
+    // the ballot becomes inactive at the overvoted rank
+    ExhaustImmediately,
+    // the overvoted rank is passed over
+    AlwaysSkipToNextRank
+}
+
+// what a voter marked at a single rank of their ballot
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum RankMark<C> {
+    Skipped,
+    Candidate(C),
+    // more than one candidate marked at the same rank
+    Overvote
+}
+
+// why a ballot was cut short while being read
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum ExhaustionReasons {
+    Overvote,
+    SkippedRanks,
+    DuplicateCandidate
+}
+
+// a ballot as it was read into the trie, and why it was cut short
+pub type ExhaustedBallot<C> = (GenericRankedVote<C>, ExhaustionReasons);
+
+// number of inactive ballots that were cut short while being read,
+// by why they were cut short
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct ExhaustionCounts {
+    pub overvotes: u64,
+    pub skipped_rankings: u64,
+    pub repeated_rankings: u64
+}
+
+impl ExhaustionCounts {
+    pub fn total(&self) -> u64 {
+        self.overvotes + self.skipped_rankings + self.repeated_rankings
+    }
+
+    fn add(&mut self, exhaustion_reason: ExhaustionReasons) {
+        match exhaustion_reason {
+            ExhaustionReasons::Overvote => self.overvotes += 1,
+            ExhaustionReasons::SkippedRanks => self.skipped_rankings += 1,
+            ExhaustionReasons::DuplicateCandidate => self.repeated_rankings += 1
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct BallotRules {
+    pub overvote_rule: OvervoteRule,
+    // consecutive skipped ranks allowed before the ballot becomes
+    // inactive, or None for no limit
+    pub max_skipped_ranks: Option<usize>,
+    // whether ranking a candidate again makes the ballot inactive
+    // (otherwise repeated rankings are passed over)
+    pub exhaust_on_duplicate_candidate: bool
+}
+
+impl Default for BallotRules {
+    fn default() -> Self {
+        BallotRules {
+            overvote_rule: OvervoteRule::ExhaustImmediately,
+            max_skipped_ranks: None,
+            exhaust_on_duplicate_candidate: false
+        }
+    }
+}
+
+impl BallotRules {
+    pub fn interpret<C: CandidateId>(
+        &self, marks: &[RankMark<C>]
+    ) -> (GenericRankedVote<C>, Option<ExhaustionReasons>) {
+        /*
+        reads a ballot's marks in rank order into a ranked vote,
+        returning why the ballot was cut short (if it was). Ballots
+        that end up ranking nobody abstain
+        */
+        let (rankings, exhaustion_reason) = self.interpret_rankings(marks);
+        let special_vote = match rankings.is_empty() {
+            true => Some(SpecialVotes::ABSTAIN),
+            false => None
+        };
+        let vote = GenericRankedVote::new(rankings, special_vote)
+            .expect("rankings are non-empty and free of duplicates");
+        (vote, exhaustion_reason)
+    }
+
+    pub fn interpret_rankings<C: CandidateId>(
+        &self, marks: &[RankMark<C>]
+    ) -> (Vec<C>, Option<ExhaustionReasons>) {
+        // the candidates a ballot's marks rank, in order, for readers
+        // that build their votes themselves
+        let mut rankings: Vec<C> = Vec::new();
+        let mut exhaustion_reason = None;
+        let mut skipped_ranks = 0;
+
+        for mark in marks {
+            let candidate = match mark {
+                RankMark::Skipped => {
+                    skipped_ranks += 1;
+                    continue;
+                }
+                RankMark::Overvote => match self.overvote_rule {
+                    OvervoteRule::ExhaustImmediately => {
+                        exhaustion_reason = Some(ExhaustionReasons::Overvote);
+                        break;
+                    }
+                    OvervoteRule::AlwaysSkipToNextRank => continue
+                },
+                RankMark::Candidate(candidate) => candidate
+            };
+
+            if self.max_skipped_ranks.is_some_and(|max| skipped_ranks > max) {
+                exhaustion_reason = Some(ExhaustionReasons::SkippedRanks);
+                break;
+            }
+            skipped_ranks = 0;
+
+            if rankings.contains(candidate) {
+                if self.exhaust_on_duplicate_candidate {
+                    exhaustion_reason =
+                        Some(ExhaustionReasons::DuplicateCandidate);
+                    break;
+                }
+                continue;
+            }
+            rankings.push(candidate.clone());
+        }
+        (rankings, exhaustion_reason)
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum TiebreakMode<C> {
+    // a tied candidate is picked at random (using the rules' seed).
+    // This isn't RCTab's own random draw, so results that come down
+    // to a random tiebreak can't be diffed against RCTab's
+    Random,
+    // the tied candidate with the fewest votes in the latest round where
+    // they differ loses, with any remaining tie broken at random
+    PreviousRoundCountsThenRandom,
+    // the tied candidate listed last in the permutation loses
+    UsePermutation(Vec<C>),
+    // a random permutation of the candidates (using the rules' seed)
+    // is generated before counting and used as with UsePermutation
+    GeneratePermutation
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct RctabRules<C> {
+    // eliminate every candidate whose combined votes can't reach the
+    // next-lowest candidate in one round
+    pub batch_elimination: bool,
+    pub tiebreak_mode: TiebreakMode<C>,
+    pub random_seed: u64,
+    // candidates with fewer votes than this are eliminated together
+    pub minimum_vote_threshold: u64
+}
+
+impl<C> Default for RctabRules<C> {
+    fn default() -> Self {
+        RctabRules {
+            batch_elimination: false,
+            tiebreak_mode: TiebreakMode::Random,
+            random_seed: 0,
+            minimum_vote_threshold: 0
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RctabRound<C> {
+    // 1-based round number
+    pub round: usize,
+    pub tallies: BTreeMap<C, u64>,
+    // votes needed to win: a majority of the continuing ballots
+    pub threshold: u64,
+    pub continuing_ballots: u64,
+    // ballots that rank no continuing candidate (including undervotes)
+    pub inactive_ballots: InactiveBallots,
+    // inactive ballots (counted in inactive_ballots) that became
+    // inactive because they were cut short while being read
+    pub inactive_by_reason: ExhaustionCounts,
+    pub elected: Option<C>,
+    // where the ballots of each candidate eliminated this round went
+    pub eliminated: BTreeMap<C, VoteFlows<C>>,
+    pub elimination_reason: Option<EliminationReasons>
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RctabResult<C> {
+    pub winner: Option<C>,
+    pub rounds: Vec<RctabRound<C>>,
+    pub total_ballots: u64,
+    // ballots that rank no candidate that isn't excluded, other than
+    // ballots that were cut short before ranking one
+    pub undervotes: u64,
+    pub excluded_candidates: Vec<C>
+}
+
+impl<C: CandidateId> RctabResult<C> {
+    pub fn to_election_result(&self) -> ElectionResult<C> {
+        ElectionResult {
+            winner: self.winner.clone(),
+            rounds: self.rounds.iter().map(|rctab_round| ElectionRound {
+                tallies: rctab_round.tallies.clone(),
+                eliminated: rctab_round.eliminated.keys().cloned().collect(),
+                elimination_reason: rctab_round.elimination_reason,
+                transfers: rctab_round.eliminated.clone(),
+                inactive_ballots: rctab_round.inactive_ballots
+            }).collect(),
+            excluded_candidates: self.excluded_candidates.clone()
+        }
+    }
+}
+
+// SplitMix64, so random tie breaks can be reproduced from a seed
+struct TiebreakRng {
+    state: u64
+}
+
+impl TiebreakRng {
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut value = self.state;
+        value = (value ^ (value >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        value = (value ^ (value >> 27)).wrapping_mul(0x94d049bb133111eb);
+        value ^ (value >> 31)
+    }
+
+    fn pick<C: Clone>(&mut self, candidates: &[C]) -> C {
+        let index = self.next_u64() % candidates.len() as u64;
+        candidates[index as usize].clone()
+    }
+}
+
+struct Tiebreaker<C> {
+    rng: TiebreakRng,
+    mode: TiebreakMode<C>,
+    // candidates in permutation order, for permutation based modes
+    permutation: Vec<C>
+}
+
+impl<C: CandidateId> Tiebreaker<C> {
+    fn new(rules: &RctabRules<C>, candidates: &HashSet<C>) -> Self {
+        let mut rng = TiebreakRng { state: rules.random_seed };
+        let permutation = match &rules.tiebreak_mode {
+            TiebreakMode::UsePermutation(permutation) => permutation.clone(),
+            TiebreakMode::GeneratePermutation => {
+                // Fisher-Yates shuffle of the sorted candidates
+                let mut permutation: Vec<C> =
+                    candidates.iter().cloned().collect();
+                permutation.sort();
+                for index in (1..permutation.len()).rev() {
+                    let other = rng.next_u64() % (index as u64 + 1);
+                    permutation.swap(index, other as usize);
+                }
+                permutation
+            }
+            _ => Vec::new()
+        };
+        Tiebreaker { rng, mode: rules.tiebreak_mode.clone(), permutation }
+    }
+
+    fn pick_loser(&mut self, tied: Vec<C>, rounds: &[RctabRound<C>]) -> C {
+        // tied candidates are sorted
+        match self.mode {
+            TiebreakMode::Random => self.rng.pick(&tied),
+            TiebreakMode::PreviousRoundCountsThenRandom => {
+                let mut tied = tied;
+                for previous_round in rounds.iter().rev() {
+                    let votes = |candidate: &C| {
+                        previous_round.tallies.get(candidate).copied()
+                            .unwrap_or(0)
+                    };
+                    let min_votes = tied.iter().map(votes).min()
+                        .expect("tied candidates are non-empty");
+                    tied.retain(|candidate| votes(candidate) == min_votes);
+                    if tied.len() == 1 { break }
+                }
+                self.rng.pick(&tied)
+            }
+            TiebreakMode::UsePermutation(_) |
+            TiebreakMode::GeneratePermutation => {
+                // candidates missing from the permutation count as last
+                let position = |candidate: &C| {
+                    self.permutation.iter().position(|other| other == candidate)
+                        .unwrap_or(usize::MAX)
+                };
+                tied.into_iter().max_by(|candidate1, candidate2| {
+                    position(candidate1).cmp(&position(candidate2))
+                        .then_with(|| candidate1.cmp(candidate2))
+                }).expect("tied candidates are non-empty")
+            }
+        }
+    }
+}
+
+fn find_batch<C: CandidateId>(tallies: &BTreeMap<C, u64>) -> Vec<C> {
+    /*
+    returns the largest group of lowest ranked candidates whose
+    combined votes are fewer than the votes of the next candidate up
+    */
+    let mut sorted_tallies: Vec<(&C, &u64)> = tallies.iter().collect();
+    sorted_tallies.sort_by_key(|(candidate, num_votes)| (**num_votes, *candidate));
+    let mut batch_size = 0;
+    let mut combined_votes = 0;
+    for index in 0..sorted_tallies.len().saturating_sub(1) {
+        combined_votes += sorted_tallies[index].1;
+        if combined_votes < *sorted_tallies[index + 1].1 {
+            batch_size = index + 1;
+        }
+    }
+    sorted_tallies[..batch_size].iter()
+        .map(|(candidate, _)| (*candidate).clone())
+        .collect()
+}
+
+fn select_eliminated<C: CandidateId>(
+    tallies: &BTreeMap<C, u64>, rules: &RctabRules<C>,
+    tiebreaker: &mut Tiebreaker<C>, rounds: &[RctabRound<C>]
+) -> (Vec<C>, EliminationReasons) {
+    let below_threshold: Vec<C> = tallies.iter()
+        .filter(|(_, num_votes)| **num_votes < rules.minimum_vote_threshold)
+        .map(|(candidate, _)| candidate.clone())
+        .collect();
+    if !below_threshold.is_empty() && below_threshold.len() < tallies.len() {
+        return (below_threshold, EliminationReasons::BelowMinimumVotes);
+    }
+
+    if rules.batch_elimination {
+        let batch = find_batch(tallies);
+        if batch.len() > 1 {
+            return (batch, EliminationReasons::BatchElimination);
+        }
+    }
+
+    let min_votes = *tallies.values().min().expect("tallies are non-empty");
+    let lowest_candidates: Vec<C> = tallies.iter()
+        .filter(|(_, num_votes)| **num_votes == min_votes)
+        .map(|(candidate, _)| candidate.clone())
+        .collect();
+    match lowest_candidates.len() {
+        1 => (lowest_candidates, EliminationReasons::FewestVotes),
+        _ => (
+            vec![tiebreaker.pick_loser(lowest_candidates, rounds)],
+            EliminationReasons::LostTiebreak
+        )
+    }
+}
+
+pub(crate) fn tabulate_rctab<C: CandidateId, T: TrieTraversal<C>>(
+    trie: &T, unique_candidates: &HashSet<C>,
+    excluded_candidates: &HashSet<C>, rules: &RctabRules<C>,
+    exhausted_ballots: &[ExhaustedBallot<C>]
+) -> RctabResult<C> {
+    let contesting_candidates: HashSet<C> = unique_candidates
+        .difference(excluded_candidates).cloned().collect();
+    let mut state = TabulationState::new(
+        trie, &contesting_candidates, excluded_candidates
+    );
+    // the contesting candidates each exhausted ballot ranks
+    let exhausted_rankings: Vec<(Vec<&C>, ExhaustionReasons)> =
+        exhausted_ballots.iter().map(|(vote, exhaustion_reason)| {
+            let rankings = vote.rankings().iter()
+                .filter(|candidate| contesting_candidates.contains(candidate))
+                .collect();
+            (rankings, *exhaustion_reason)
+        }).collect();
+    // ballots cut short before ranking anyone are inactive from the
+    // first round rather than undervotes, as in RCTab
+    let num_unranked_exhausted = exhausted_rankings.iter()
+        .filter(|(rankings, _)| rankings.is_empty())
+        .count() as u64;
+    let total_ballots = trie.node_votes(trie.root_node());
+    let undervotes = (total_ballots - state.effective_total_votes)
+        .saturating_sub(num_unranked_exhausted);
+    let mut tiebreaker = Tiebreaker::new(rules, &contesting_candidates);
+    let mut rounds: Vec<RctabRound<C>> = Vec::new();
+
+    let winner = loop {
+        let tallies: BTreeMap<C, u64> = state.candidate_vote_counts.iter()
+            .map(|(candidate, num_votes)| (candidate.clone(), *num_votes))
+            .collect();
+        if tallies.is_empty() { break None }
+
+        let continuing_ballots: u64 = tallies.values().sum();
+        let mut inactive_by_reason = ExhaustionCounts::default();
+        for (rankings, exhaustion_reason) in &exhausted_rankings {
+            if !rankings.iter().any(|candidate| tallies.contains_key(*candidate)) {
+                inactive_by_reason.add(*exhaustion_reason);
+            }
+        }
+        let mut rctab_round = RctabRound {
+            round: rounds.len() + 1,
+            threshold: continuing_ballots / 2 + 1,
+            continuing_ballots,
+            inactive_ballots: state.inactive_ballots,
+            inactive_by_reason,
+            elected: None,
+            eliminated: BTreeMap::new(),
+            elimination_reason: None,
+            tallies
+        };
+
+        let leader = rctab_round.tallies.iter()
+            .max_by_key(|(candidate, num_votes)| (**num_votes, *candidate))
+            .map(|(candidate, num_votes)| (candidate.clone(), *num_votes));
+        if let Some((leader, leader_votes)) = leader {
+            let is_last_candidate = rctab_round.tallies.len() == 1;
+            if is_last_candidate || leader_votes >= rctab_round.threshold {
+                rctab_round.elected = Some(leader.clone());
+                rounds.push(rctab_round);
+                break Some(leader);
+            }
+        }
+
+        let (eliminated, elimination_reason) = select_eliminated(
+            &rctab_round.tallies, rules, &mut tiebreaker, &rounds
+        );
+        rctab_round.eliminated = state.eliminate(&eliminated);
+        rctab_round.elimination_reason = Some(elimination_reason);
+        rounds.push(rctab_round);
+    };
+
+    let mut excluded_candidates: Vec<C> =
+        excluded_candidates.iter().cloned().collect();
+    excluded_candidates.sort();
+    RctabResult {
+        winner, rounds, total_ballots, undervotes, excluded_candidates
+    }
+}
+
+impl<C: CandidateId> GenericRankedChoiceVoteTrie<C> {
+    pub fn tabulate_rctab(&self, rules: &RctabRules<C>) -> RctabResult<C> {
+        /*
+        tabulates the election using RCTab's single winner rules.
+        The trie's elimination strategy is not used, but excluded
+        candidates are still skipped over on every ballot
+        */
+        self.tabulate_rctab_with_exhausted_ballots(rules, &[])
+    }
+
+    pub fn tabulate_rctab_with_exhausted_ballots(
+        &self, rules: &RctabRules<C>, exhausted_ballots: &[ExhaustedBallot<C>]
+    ) -> RctabResult<C> {
+        /*
+        tabulates as with tabulate_rctab, additionally counting the
+        inactive ballots in each round by why they were cut short
+        while being read (e.g. CsvImportLog::exhausted_ballots).
+        Every exhausted ballot must also have been inserted in the trie
+        */
+        tabulate_rctab(
+            self, &self.unique_candidates, &self.excluded_candidates, rules,
+            exhausted_ballots
+        )
+    }
+}
+
+impl<C: CandidateId> GenericArenaVoteTrie<C> {
+    pub fn tabulate_rctab(&self, rules: &RctabRules<C>) -> RctabResult<C> {
+        self.tabulate_rctab_with_exhausted_ballots(rules, &[])
+    }
+
+    pub fn tabulate_rctab_with_exhausted_ballots(
+        &self, rules: &RctabRules<C>, exhausted_ballots: &[ExhaustedBallot<C>]
+    ) -> RctabResult<C> {
+        tabulate_rctab(
+            self, &self.unique_candidates, &self.excluded_candidates, rules,
+            exhausted_ballots
+        )
+    }
+}
+
+// contest details shown at the top of RCTab summaries
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct RctabSummaryConfig {
+    pub contest: String,
+    pub jurisdiction: String,
+    pub office: String,
+    pub date: String
+}
+
+fn num_inactive_ballots<C>(
+    result: &RctabResult<C>, rctab_round: &RctabRound<C>
+) -> u64 {
+    // RCTab doesn't count undervotes as inactive ballots
+    rctab_round.inactive_ballots.total().saturating_sub(result.undervotes)
+}
+
+fn num_exhausted_choices<C>(
+    result: &RctabResult<C>, rctab_round: &RctabRound<C>
+) -> u64 {
+    // inactive ballots that simply ran out of continuing candidates
+    num_inactive_ballots(result, rctab_round)
+        .saturating_sub(rctab_round.inactive_by_reason.total())
+}
+
+fn generated_by() -> String {
+    format!("trie_rcv {}", env!("CARGO_PKG_VERSION"))
+}
+
+pub fn summary_json<C, F>(
+    result: &RctabResult<C>, config: &RctabSummaryConfig, candidate_name: F
+) -> String
+where C: CandidateId, F: Fn(&C) -> String {
+    /*
+    renders the result in the layout of RCTab's summary.json. As in
+    RCTab, vote counts are written as strings
+    */
+    let count = |num_votes: u64| JsonValue::from(num_votes.to_string());
+    let results = result.rounds.iter().map(|rctab_round| {
+        let inactive_by_reason = &rctab_round.inactive_by_reason;
+        let mut tally_results: Vec<JsonValue> = Vec::new();
+        for (candidate, vote_flows) in &rctab_round.eliminated {
+            let mut transfers: Vec<(String, JsonValue)> = vote_flows.transfers
+                .iter().map(|(next_candidate, num_votes)| {
+                    (candidate_name(next_candidate), count(*num_votes))
+                }).collect();
+            let exhausted_votes = vote_flows.exhausted_votes +
+                vote_flows.withhold_votes + vote_flows.abstain_votes;
+            if exhausted_votes > 0 {
+                transfers.push(("exhausted".to_string(), count(exhausted_votes)));
+            }
+            tally_results.push(JsonValue::Object(vec![
+                ("eliminated".to_string(), candidate_name(candidate).into()),
+                ("transfers".to_string(), JsonValue::Object(transfers))
+            ]));
+        }
+        if let Some(elected) = &rctab_round.elected {
+            tally_results.push(JsonValue::Object(vec![
+                ("elected".to_string(), candidate_name(elected).into()),
+                ("transfers".to_string(), JsonValue::Object(vec![]))
+            ]));
+        }
+
+        JsonValue::Object(vec![
+            ("inactiveBallots".to_string(), JsonValue::Object(vec![
+                ("exhaustedChoices".to_string(), count(
+                    num_exhausted_choices(result, rctab_round)
+                )),
+                ("overvotes".to_string(), count(inactive_by_reason.overvotes)),
+                ("repeatedRankings".to_string(), count(
+                    inactive_by_reason.repeated_rankings
+                )),
+                ("skippedRankings".to_string(), count(
+                    inactive_by_reason.skipped_rankings
+                ))
+            ])),
+            ("round".to_string(), rctab_round.round.into()),
+            ("tally".to_string(), JsonValue::Object(
+                rctab_round.tallies.iter().map(|(candidate, num_votes)| {
+                    (candidate_name(candidate), count(*num_votes))
+                }).collect()
+            )),
+            ("tallyResults".to_string(), JsonValue::Array(tally_results)),
+            ("threshold".to_string(), count(rctab_round.threshold))
+        ])
+    }).collect();
+
+    let final_threshold = result.rounds.last()
+        .map_or(0, |rctab_round| rctab_round.threshold);
+    let num_candidates = result.rounds.first()
+        .map_or(0, |rctab_round| rctab_round.tallies.len());
+    JsonValue::Object(vec![
+        ("config".to_string(), JsonValue::Object(vec![
+            ("contest".to_string(), config.contest.as_str().into()),
+            ("date".to_string(), config.date.as_str().into()),
+            ("generatedBy".to_string(), generated_by().into()),
+            ("jurisdiction".to_string(), config.jurisdiction.as_str().into()),
+            ("office".to_string(), config.office.as_str().into())
+        ])),
+        ("results".to_string(), JsonValue::Array(results)),
+        ("summary".to_string(), JsonValue::Object(vec![
+            ("finalThreshold".to_string(), count(final_threshold)),
+            ("numCandidates".to_string(), num_candidates.into()),
+            ("numWinners".to_string(), 1usize.into()),
+            ("totalNumBallots".to_string(), count(result.total_ballots)),
+            ("undervotes".to_string(), result.undervotes.into())
+        ]))
+    ]).to_pretty_string()
+}
+
+fn csv_field(text: &str) -> String {
+    match text.contains([',', '"', '\n']) {
+        true => format!("\"{}\"", text.replace('"', "\"\"")),
+        false => text.to_string()
+    }
+}
+
+pub fn summary_csv<C, F>(
+    result: &RctabResult<C>, config: &RctabSummaryConfig, candidate_name: F
+) -> String
+where C: CandidateId, F: Fn(&C) -> String {
+    /*
+    renders the result in the layout of RCTab's summary.csv: contest
+    details, then one row per candidate with the votes, vote share
+    and votes transferred in each round
+    */
+    let name = |candidate: &C| csv_field(&candidate_name(candidate));
+    let mut csv = String::new();
+    let winner_name = result.winner.as_ref().map(name).unwrap_or_default();
+    let final_threshold = result.rounds.last()
+        .map_or(0, |rctab_round| rctab_round.threshold);
+    // candidates are listed by first round votes, as in RCTab
+    let candidates: Vec<(&C, u64)> = result.rounds.first()
+        .map(|first_round| sort_by_votes(&first_round.tallies))
+        .unwrap_or_default();
+
+    let lines = [
+        "Contest Information".to_string(),
+        format!("Generated By,{}", generated_by()),
+        format!("Contest,{}", csv_field(&config.contest)),
+        format!("Jurisdiction,{}", csv_field(&config.jurisdiction)),
+        format!("Office,{}", csv_field(&config.office)),
+        format!("Date,{}", csv_field(&config.date)),
+        format!("Winner(s),{}", winner_name),
+        format!("Final Threshold,{}", final_threshold),
+        String::new(),
+        "Contest Summary".to_string(),
+        "Number to be Elected,1".to_string(),
+        format!("Number of Candidates,{}", candidates.len()),
+        format!("Total Number of Ballots,{}", result.total_ballots),
+        format!("Number of Undervotes (No Rankings),{}", result.undervotes),
+        String::new()
+    ];
+    for line in lines {
+        writeln!(csv, "{}", line).expect("writing to a string cannot fail");
+    }
+
+    let mut rows: Vec<Vec<String>> = vec![
+        vec!["Rounds".to_string()], vec!["Eliminated".to_string()],
+        vec!["Elected".to_string()]
+    ];
+    for (index, rctab_round) in result.rounds.iter().enumerate() {
+        let round = rctab_round.round;
+        rows[0].extend([
+            format!("Round {} Votes", round), "% of vote".to_string(),
+            "transfer".to_string()
+        ]);
+        let eliminated: Vec<String> =
+            rctab_round.eliminated.keys().map(name).collect();
+        rows[1].extend([eliminated.join("; "), String::new(), String::new()]);
+        let elected = rctab_round.elected.as_ref().map(name).unwrap_or_default();
+        rows[2].extend([elected, String::new(), String::new()]);
+
+        let next_round = result.rounds.get(index + 1);
+        for (row_index, (candidate, _)) in candidates.iter().enumerate() {
+            if index == 0 { rows.push(vec![name(candidate)]); }
+            let num_votes = rctab_round.tallies.get(*candidate).copied();
+            let share = match (num_votes, rctab_round.continuing_ballots) {
+                (Some(num_votes), continuing) if continuing > 0 => format!(
+                    "{:.2}%", num_votes as f64 * 100.0 / continuing as f64
+                ),
+                _ => String::new()
+            };
+            // change in votes going into the next round
+            let transfer = match (num_votes, next_round) {
+                (Some(num_votes), Some(next_round)) => {
+                    let next_votes = next_round.tallies.get(*candidate)
+                        .copied().unwrap_or(0);
+                    (next_votes as i64 - num_votes as i64).to_string()
+                }
+                _ => String::new()
+            };
+            rows[3 + row_index].extend([
+                num_votes.map(|num_votes| num_votes.to_string())
+                    .unwrap_or_default(),
+                share, transfer
+            ]);
+        }
+    }
+
+    let mut footer_rows: Vec<Vec<String>> = vec![
+        vec!["Active Ballots".to_string()],
+        vec!["Current Round Threshold".to_string()],
+        vec!["Inactive Ballots by Overvotes".to_string()],
+        vec!["Inactive Ballots by Skipped Rankings".to_string()],
+        vec!["Inactive Ballots by Exhausted Choices".to_string()],
+        vec!["Inactive Ballots by Repeated Rankings".to_string()],
+        vec!["Inactive Ballots Total".to_string()]
+    ];
+    for rctab_round in &result.rounds {
+        let inactive_by_reason = &rctab_round.inactive_by_reason;
+        let values = [
+            rctab_round.continuing_ballots, rctab_round.threshold,
+            inactive_by_reason.overvotes, inactive_by_reason.skipped_rankings,
+            num_exhausted_choices(result, rctab_round),
+            inactive_by_reason.repeated_rankings,
+            num_inactive_ballots(result, rctab_round)
+        ];
+        for (row, value) in footer_rows.iter_mut().zip(values) {
+            row.extend([value.to_string(), String::new(), String::new()]);
+        }
+    }
+
+    for row in rows.iter().chain(&footer_rows) {
+        writeln!(csv, "{}", row.join(",")).expect("writing to a string cannot fail");
+    }
+    csv
+}
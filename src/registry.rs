@@ -0,0 +1,64 @@
+use std::collections::HashMap;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CandidateRegistry {
+    /*
+    assigns candidate ids (counting from 1) to candidate names, so
+    ballots that name candidates can be stored as RankedVotes and
+    tabulation results can be reported by name
+    */
+    names: Vec<String>,
+    ids: HashMap<String, u32>
+}
+
+impl CandidateRegistry {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn from_names<S: AsRef<str>>(names: &[S]) -> Self {
+        let mut registry = Self::new();
+        for name in names {
+            registry.register(name.as_ref());
+        }
+        registry
+    }
+
+    pub fn register(&mut self, name: &str) -> u32 {
+        // returns the existing id if the name is already registered
+        if let Some(id) = self.ids.get(name) {
+            return *id;
+        }
+        self.names.push(name.to_string());
+        let id = u32::try_from(self.names.len())
+            .expect("too many candidates to register");
+        self.ids.insert(name.to_string(), id);
+        id
+    }
+
+    pub fn get_id(&self, name: &str) -> Option<u32> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn get_name(&self, id: u32) -> Option<&str> {
+        let index = usize::try_from(id).ok()?.checked_sub(1)?;
+        self.names.get(index).map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn candidate_ids(&self) -> Vec<u32> {
+        (1..=self.names.len() as u32).collect()
+    }
+
+    pub fn names(&self) -> &[String] {
+        // candidate names in id order
+        &self.names
+    }
+}
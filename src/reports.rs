@@ -0,0 +1,342 @@
+use crate::{
+    sort_by_votes, CandidateId, ElectionResult, EliminationReasons,
+    InactiveBallots, VoteFlows
+};
+use crate::json::JsonValue;
+
+/*
+renders tabulation results for publishing: stable JSON for APIs, and
+Markdown or HTML tables for posting. Every renderer takes a function
+that names candidates, so results keyed by candidate id can be shown
+with candidate names (e.g. CandidateRegistry::get_name)
+*/
+
+pub fn reason_key(reason: EliminationReasons) -> &'static str {
+    // identifier of the elimination reason used in JSON reports
+    match reason {
+        EliminationReasons::FewestVotes => "fewest_votes",
+        EliminationReasons::LowestDowdallScore => "lowest_dowdall_score",
+        EliminationReasons::LostRankedPairs => "lost_ranked_pairs",
+        EliminationReasons::LostCondorcetRankedPairs => {
+            "lost_condorcet_ranked_pairs"
+        },
+        EliminationReasons::BelowMinimumVotes => "below_minimum_votes",
+        EliminationReasons::BatchElimination => "batch_elimination",
+        EliminationReasons::LostTiebreak => "lost_tiebreak"
+    }
+}
+
+pub fn reason_description(reason: EliminationReasons) -> &'static str {
+    match reason {
+        EliminationReasons::FewestVotes => "fewest votes",
+        EliminationReasons::LowestDowdallScore => {
+            "tied for fewest votes, lowest Dowdall score"
+        },
+        EliminationReasons::LostRankedPairs => {
+            "tied for fewest votes, lost head-to-head"
+        },
+        EliminationReasons::LostCondorcetRankedPairs => {
+            "lost head-to-head among the lowest candidates"
+        },
+        EliminationReasons::BelowMinimumVotes => "below the minimum vote threshold",
+        EliminationReasons::BatchElimination => "batch elimination",
+        EliminationReasons::LostTiebreak => "tied for fewest votes, lost tie break"
+    }
+}
+
+fn inactive_json(inactive_ballots: &InactiveBallots) -> JsonValue {
+    JsonValue::Object(vec![
+        ("withhold".to_string(), inactive_ballots.withhold_votes.into()),
+        ("abstain".to_string(), inactive_ballots.abstain_votes.into()),
+        ("exhausted".to_string(), inactive_ballots.exhausted_votes.into()),
+        ("total".to_string(), inactive_ballots.total().into())
+    ])
+}
+
+pub fn report_json<C, F>(result: &ElectionResult<C>, candidate_name: F) -> String
+where C: CandidateId, F: Fn(&C) -> String {
+    /*
+    fields and list entries are always written in the same order
+    (tallies from most to fewest votes) so that reports of the same
+    result are identical and can be diffed
+    */
+    let candidate_json = |candidate: &C, num_votes: u64| JsonValue::Object(vec![
+        ("candidate".to_string(), candidate_name(candidate).into()),
+        ("votes".to_string(), num_votes.into())
+    ]);
+    let eliminated_json = |candidate: &C, vote_flows: Option<&VoteFlows<C>>| {
+        let default_flows = VoteFlows::default();
+        let vote_flows = vote_flows.unwrap_or(&default_flows);
+        JsonValue::Object(vec![
+            ("candidate".to_string(), candidate_name(candidate).into()),
+            ("transfers".to_string(), JsonValue::Array(
+                vote_flows.transfers.iter().map(|(next_candidate, num_votes)| {
+                    candidate_json(next_candidate, *num_votes)
+                }).collect()
+            )),
+            ("withhold".to_string(), vote_flows.withhold_votes.into()),
+            ("abstain".to_string(), vote_flows.abstain_votes.into()),
+            ("exhausted".to_string(), vote_flows.exhausted_votes.into())
+        ])
+    };
+
+    let rounds = result.rounds.iter().enumerate().map(|(index, round)| {
+        let active_votes: u64 = round.tallies.values().sum();
+        JsonValue::Object(vec![
+            ("round".to_string(), (index + 1).into()),
+            ("tallies".to_string(), JsonValue::Array(
+                round.sorted_tallies().into_iter()
+                    .map(|(candidate, num_votes)| candidate_json(candidate, num_votes))
+                    .collect()
+            )),
+            ("active_votes".to_string(), active_votes.into()),
+            ("inactive_ballots".to_string(), inactive_json(&round.inactive_ballots)),
+            ("eliminated".to_string(), JsonValue::Array(
+                round.eliminated.iter().map(|candidate| {
+                    eliminated_json(candidate, round.transfers.get(candidate))
+                }).collect()
+            )),
+            ("elimination_reason".to_string(),
+                round.elimination_reason.map(reason_key).into())
+        ])
+    }).collect();
+
+    JsonValue::Object(vec![
+        ("winner".to_string(), result.winner.as_ref().map(&candidate_name).into()),
+        ("excluded_candidates".to_string(), JsonValue::Array(
+            result.excluded_candidates.iter()
+                .map(|candidate| candidate_name(candidate).into())
+                .collect()
+        )),
+        ("rounds".to_string(), JsonValue::Array(rounds))
+    ]).to_pretty_string()
+}
+
+type InactiveCount = fn(&InactiveBallots) -> u64;
+
+// table shared by the Markdown and HTML renderers
+struct ReportTable {
+    headers: Vec<String>,
+    // whether each column holds numbers (and is right aligned)
+    numeric_columns: Vec<bool>,
+    rows: Vec<Vec<String>>
+}
+
+fn rounds_table<C, F>(result: &ElectionResult<C>, candidate_name: &F) -> ReportTable
+where C: CandidateId, F: Fn(&C) -> String {
+    /*
+    one row per candidate with their votes in every round (and the
+    votes they gained since the last round), followed by the running
+    counts of inactive ballots
+    */
+    let mut candidates: Vec<&C> = Vec::new();
+    for round in &result.rounds {
+        for (candidate, _) in round.sorted_tallies() {
+            if !candidates.contains(&candidate) { candidates.push(candidate); }
+        }
+    }
+
+    let num_rounds = result.rounds.len();
+    let mut headers = vec!["Candidate".to_string()];
+    headers.extend((1..=num_rounds).map(|round| format!("Round {}", round)));
+    let mut numeric_columns = vec![false];
+    numeric_columns.extend(vec![true; num_rounds]);
+
+    let mut rows: Vec<Vec<String>> = candidates.iter().map(|candidate| {
+        let mut row = vec![candidate_name(candidate)];
+        let mut previous_votes: Option<u64> = None;
+        for round in &result.rounds {
+            let Some(&num_votes) = round.tallies.get(*candidate) else {
+                row.push(String::new());
+                previous_votes = None;
+                continue;
+            };
+            row.push(match previous_votes {
+                Some(previous_votes) if num_votes > previous_votes => {
+                    format!("{} (+{})", num_votes, num_votes - previous_votes)
+                }
+                _ => num_votes.to_string()
+            });
+            previous_votes = Some(num_votes);
+        }
+        row
+    }).collect();
+
+    let inactive_rows: [(&str, InactiveCount); 3] = [
+        ("Withheld", |inactive| inactive.withhold_votes),
+        ("Abstained", |inactive| inactive.abstain_votes),
+        ("Exhausted", |inactive| inactive.exhausted_votes)
+    ];
+    for (label, count) in inactive_rows {
+        let mut row = vec![label.to_string()];
+        row.extend(result.rounds.iter().map(|round| {
+            count(&round.inactive_ballots).to_string()
+        }));
+        rows.push(row);
+    }
+    ReportTable { headers, numeric_columns, rows }
+}
+
+fn eliminations_table<C, F>(
+    result: &ElectionResult<C>, candidate_name: &F
+) -> ReportTable
+where C: CandidateId, F: Fn(&C) -> String {
+    // one row per eliminated candidate, with where their ballots went
+    let headers = [
+        "Round", "Eliminated", "Reason", "Transfers", "Withheld", "Abstained",
+        "Exhausted"
+    ].map(String::from).to_vec();
+    let numeric_columns = vec![true, false, false, false, true, true, true];
+
+    let mut rows = Vec::new();
+    for (index, round) in result.rounds.iter().enumerate() {
+        let reason = round.elimination_reason
+            .map_or("", reason_description).to_string();
+        for candidate in &round.eliminated {
+            let default_flows = VoteFlows::default();
+            let vote_flows = round.transfers.get(candidate)
+                .unwrap_or(&default_flows);
+            let transfers: Vec<String> = sort_by_votes(&vote_flows.transfers)
+                .into_iter()
+                .map(|(next_candidate, num_votes)| {
+                    format!("{}: {}", candidate_name(next_candidate), num_votes)
+                }).collect();
+
+            rows.push(vec![
+                (index + 1).to_string(), candidate_name(candidate),
+                reason.clone(), transfers.join(", "),
+                vote_flows.withhold_votes.to_string(),
+                vote_flows.abstain_votes.to_string(),
+                vote_flows.exhausted_votes.to_string()
+            ]);
+        }
+    }
+    ReportTable { headers, numeric_columns, rows }
+}
+
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '|' | '\\' | '*' | '_' | '`' | '[' | ']' | '<' | '>' => {
+                escaped.push('\\');
+                escaped.push(character);
+            }
+            '\n' | '\r' => escaped.push(' '),
+            character => escaped.push(character)
+        }
+    }
+    escaped
+}
+
+pub(crate) fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            character => escaped.push(character)
+        }
+    }
+    escaped
+}
+
+impl ReportTable {
+    fn to_markdown(&self) -> String {
+        let to_line = |cells: Vec<String>| format!("| {} |\n", cells.join(" | "));
+        let mut markdown = to_line(
+            self.headers.iter().map(|header| escape_markdown(header)).collect()
+        );
+        markdown.push_str(&to_line(self.numeric_columns.iter().map(|numeric| {
+            match numeric {
+                true => "---:".to_string(),
+                false => "---".to_string()
+            }
+        }).collect()));
+        for row in &self.rows {
+            markdown.push_str(&to_line(
+                row.iter().map(|cell| escape_markdown(cell)).collect()
+            ));
+        }
+        markdown
+    }
+
+    fn to_html(&self, class: &str) -> String {
+        let cell = |tag: &str, index: usize, text: &str| {
+            match self.numeric_columns[index] {
+                true => format!(
+                    "<{} class=\"number\">{}</{}>", tag, escape_html(text), tag
+                ),
+                false => format!("<{}>{}</{}>", tag, escape_html(text), tag)
+            }
+        };
+        let mut html = format!("<table class=\"{}\">\n<thead>\n<tr>", class);
+        for (index, header) in self.headers.iter().enumerate() {
+            html.push_str(&cell("th", index, header));
+        }
+        html.push_str("</tr>\n</thead>\n<tbody>\n");
+        for row in &self.rows {
+            html.push_str("<tr>");
+            for (index, text) in row.iter().enumerate() {
+                html.push_str(&cell("td", index, text));
+            }
+            html.push_str("</tr>\n");
+        }
+        html.push_str("</tbody>\n</table>\n");
+        html
+    }
+}
+
+fn summary_lines<C, F>(
+    result: &ElectionResult<C>, candidate_name: &F
+) -> Vec<(&'static str, String)>
+where C: CandidateId, F: Fn(&C) -> String {
+    let mut lines = vec![(
+        "Winner",
+        result.winner.as_ref().map_or("none".to_string(), candidate_name)
+    )];
+    if !result.excluded_candidates.is_empty() {
+        let excluded: Vec<String> =
+            result.excluded_candidates.iter().map(candidate_name).collect();
+        lines.push(("Excluded candidates", excluded.join(", ")));
+    }
+    lines
+}
+
+pub fn report_markdown<C, F>(
+    result: &ElectionResult<C>, candidate_name: F
+) -> String
+where C: CandidateId, F: Fn(&C) -> String {
+    let mut markdown = String::new();
+    for (label, value) in summary_lines(result, &candidate_name) {
+        markdown.push_str(&format!("**{}:** {}\n\n", label, escape_markdown(&value)));
+    }
+    markdown.push_str("### Rounds\n\n");
+    markdown.push_str(&rounds_table(result, &candidate_name).to_markdown());
+    markdown.push_str("\n### Eliminations\n\n");
+    markdown.push_str(&eliminations_table(result, &candidate_name).to_markdown());
+    markdown
+}
+
+pub fn report_html<C, F>(result: &ElectionResult<C>, candidate_name: F) -> String
+where C: CandidateId, F: Fn(&C) -> String {
+    // an HTML fragment to embed in a page, which can style the tables
+    // by their rcv-rounds and rcv-eliminations classes
+    let mut html = String::from("<section class=\"rcv-report\">\n");
+    for (label, value) in summary_lines(result, &candidate_name) {
+        html.push_str(&format!(
+            "<p><strong>{}:</strong> {}</p>\n", label, escape_html(&value)
+        ));
+    }
+    html.push_str("<h3>Rounds</h3>\n");
+    html.push_str(&rounds_table(result, &candidate_name).to_html("rcv-rounds"));
+    html.push_str("<h3>Eliminations</h3>\n");
+    html.push_str(
+        &eliminations_table(result, &candidate_name).to_html("rcv-eliminations")
+    );
+    html.push_str("</section>\n");
+    html
+}
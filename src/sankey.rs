@@ -0,0 +1,409 @@
+use std::collections::HashMap;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use crate::{CandidateId, ElectionResult, InactiveBallots, VoteFlows};
+use crate::json::JsonValue;
+use crate::reports::escape_html;
+
+/*
+turns the vote flows of a tabulation into a Sankey diagram, with one
+column of nodes per round. Candidates carry their votes over to their
+node in the next round, while the ballots of eliminated candidates
+flow to the candidates they transfer to or to the withheld, abstained
+and exhausted sink nodes (which also carry over from round to round)
+*/
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SankeyNodeKinds {
+    Candidate,
+    Withhold,
+    Abstain,
+    Exhausted
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SankeyLinkKinds {
+    // votes staying with the same candidate (or sink) between rounds
+    Continuing,
+    // votes moving on from an eliminated candidate
+    Transfer
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SankeyNode {
+    pub name: String,
+    // 1-based round (i.e. column) of the node
+    pub round: usize,
+    pub kind: SankeyNodeKinds,
+    pub value: u64
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SankeyLink {
+    // indexes of the source and target nodes
+    pub source: usize,
+    pub target: usize,
+    pub value: u64,
+    pub kind: SankeyLinkKinds
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SankeyDiagram {
+    pub nodes: Vec<SankeyNode>,
+    pub links: Vec<SankeyLink>
+}
+
+const SINKS: [(SankeyNodeKinds, &str); 3] = [
+    (SankeyNodeKinds::Withhold, "Withheld"),
+    (SankeyNodeKinds::Abstain, "Abstained"),
+    (SankeyNodeKinds::Exhausted, "Exhausted")
+];
+
+fn sink_votes(kind: SankeyNodeKinds, withhold: u64, abstain: u64, exhausted: u64) -> u64 {
+    match kind {
+        SankeyNodeKinds::Withhold => withhold,
+        SankeyNodeKinds::Abstain => abstain,
+        SankeyNodeKinds::Exhausted => exhausted,
+        SankeyNodeKinds::Candidate => 0
+    }
+}
+
+fn inactive_votes(inactive_ballots: &InactiveBallots, kind: SankeyNodeKinds) -> u64 {
+    sink_votes(
+        kind, inactive_ballots.withhold_votes, inactive_ballots.abstain_votes,
+        inactive_ballots.exhausted_votes
+    )
+}
+
+fn flow_votes<C>(vote_flows: &VoteFlows<C>, kind: SankeyNodeKinds) -> u64 {
+    sink_votes(
+        kind, vote_flows.withhold_votes, vote_flows.abstain_votes,
+        vote_flows.exhausted_votes
+    )
+}
+
+// a node within a column: a candidate, or a sink (with no candidate)
+type ColumnKey<'a, C> = (SankeyNodeKinds, Option<&'a C>);
+
+impl SankeyDiagram {
+    fn add_column<'a, C, F>(
+        &mut self, round: usize, tallies: &[(&'a C, u64)],
+        inactive_ballots: &InactiveBallots, candidate_name: &F
+    ) -> HashMap<ColumnKey<'a, C>, usize>
+    where C: CandidateId, F: Fn(&C) -> String {
+        // candidates from most to fewest votes, then non-empty sinks
+        let mut column = HashMap::new();
+        for (candidate, num_votes) in tallies {
+            column.insert((SankeyNodeKinds::Candidate, Some(*candidate)), self.nodes.len());
+            self.nodes.push(SankeyNode {
+                name: candidate_name(candidate), round,
+                kind: SankeyNodeKinds::Candidate, value: *num_votes
+            });
+        }
+        for (kind, name) in SINKS {
+            let num_votes = inactive_votes(inactive_ballots, kind);
+            if num_votes == 0 { continue }
+            column.insert((kind, None), self.nodes.len());
+            self.nodes.push(SankeyNode {
+                name: name.to_string(), round, kind, value: num_votes
+            });
+        }
+        column
+    }
+
+    fn add_link(
+        &mut self, source: Option<&usize>, target: Option<&usize>,
+        value: u64, kind: SankeyLinkKinds
+    ) {
+        if let (Some(&source), Some(&target)) = (source, target) {
+            if value > 0 {
+                self.links.push(SankeyLink { source, target, value, kind });
+            }
+        }
+    }
+
+    pub fn from_result<C, F>(result: &ElectionResult<C>, candidate_name: F) -> Self
+    where C: CandidateId, F: Fn(&C) -> String {
+        /*
+        builds one column per round. If the last round eliminated
+        candidates (e.g. everyone left was tied) an extra column shows
+        where their ballots went
+        */
+        let mut diagram = SankeyDiagram::default();
+        let Some(first_round) = result.rounds.first() else { return diagram };
+        let mut column = diagram.add_column(
+            1, &first_round.sorted_tallies(), &first_round.inactive_ballots, &candidate_name
+        );
+
+        for (index, round) in result.rounds.iter().enumerate() {
+            let next_column = match result.rounds.get(index + 1) {
+                Some(next_round) => diagram.add_column(
+                    index + 2, &result.rounds[index + 1].sorted_tallies(),
+                    &next_round.inactive_ballots, &candidate_name
+                ),
+                None if !round.eliminated.is_empty() => {
+                    let mut inactive_ballots = round.inactive_ballots;
+                    for vote_flows in round.transfers.values() {
+                        inactive_ballots.add_flows(vote_flows);
+                    }
+                    diagram.add_column(
+                        index + 2, &[], &inactive_ballots, &candidate_name
+                    )
+                }
+                None => break
+            };
+
+            for (candidate, num_votes) in &round.tallies {
+                if round.eliminated.contains(candidate) { continue }
+                let key = (SankeyNodeKinds::Candidate, Some(candidate));
+                diagram.add_link(
+                    column.get(&key), next_column.get(&key), *num_votes,
+                    SankeyLinkKinds::Continuing
+                );
+            }
+            for (kind, _) in SINKS {
+                diagram.add_link(
+                    column.get(&(kind, None)), next_column.get(&(kind, None)),
+                    inactive_votes(&round.inactive_ballots, kind),
+                    SankeyLinkKinds::Continuing
+                );
+            }
+
+            for (candidate, vote_flows) in &round.transfers {
+                let source = column.get(&(SankeyNodeKinds::Candidate, Some(candidate)));
+                for (next_candidate, num_votes) in &vote_flows.transfers {
+                    let key = (SankeyNodeKinds::Candidate, Some(next_candidate));
+                    diagram.add_link(
+                        source, next_column.get(&key), *num_votes,
+                        SankeyLinkKinds::Transfer
+                    );
+                }
+                for (kind, _) in SINKS {
+                    diagram.add_link(
+                        source, next_column.get(&(kind, None)),
+                        flow_votes(vote_flows, kind), SankeyLinkKinds::Transfer
+                    );
+                }
+            }
+            column = next_column;
+        }
+        diagram
+    }
+
+    pub fn to_json(&self) -> String {
+        /*
+        nodes and links in the shape d3-sankey expects, with links
+        pointing at nodes by their index in the node list
+        */
+        let kind_name = |kind: SankeyNodeKinds| match kind {
+            SankeyNodeKinds::Candidate => "candidate",
+            SankeyNodeKinds::Withhold => "withhold",
+            SankeyNodeKinds::Abstain => "abstain",
+            SankeyNodeKinds::Exhausted => "exhausted"
+        };
+        let nodes = self.nodes.iter().map(|node| JsonValue::Object(vec![
+            ("name".to_string(), node.name.as_str().into()),
+            ("round".to_string(), node.round.into()),
+            ("kind".to_string(), kind_name(node.kind).into()),
+            ("value".to_string(), node.value.into())
+        ])).collect();
+        let links = self.links.iter().map(|link| JsonValue::Object(vec![
+            ("source".to_string(), link.source.into()),
+            ("target".to_string(), link.target.into()),
+            ("value".to_string(), link.value.into()),
+            ("kind".to_string(), match link.kind {
+                SankeyLinkKinds::Continuing => "continuing",
+                SankeyLinkKinds::Transfer => "transfer"
+            }.into())
+        ])).collect();
+
+        JsonValue::Object(vec![
+            ("nodes".to_string(), JsonValue::Array(nodes)),
+            ("links".to_string(), JsonValue::Array(links))
+        ]).to_pretty_string()
+    }
+
+    pub fn to_svg(&self) -> String {
+        /*
+        draws the diagram as a standalone SVG image (no scripts or
+        external styles), with node heights proportional to votes
+        */
+        const MARGIN: f64 = 24.0;
+        const HEADER_HEIGHT: f64 = 24.0;
+        const NODE_WIDTH: f64 = 16.0;
+        const NODE_PADDING: f64 = 12.0;
+        const COLUMN_GAP: f64 = 200.0;
+        const LABEL_WIDTH: f64 = 160.0;
+        const PALETTE: [&str; 9] = [
+            "#1f77b4", "#ff7f0e", "#2ca02c", "#d62728", "#9467bd", "#8c564b",
+            "#e377c2", "#bcbd22", "#17becf"
+        ];
+
+        // the fields are public (and may be deserialized), so nodes
+        // outside the rounds a diagram this size can have and links to
+        // missing nodes are left out rather than trusted
+        let is_drawn = |index: usize| self.nodes.get(index).is_some_and(
+            |node| (1..=self.nodes.len()).contains(&node.round)
+        );
+        let links: Vec<(usize, &SankeyLink)> = self.links.iter().enumerate()
+            .filter(|(_, link)| is_drawn(link.source) && is_drawn(link.target))
+            .collect();
+
+        let num_columns = (0..self.nodes.len()).filter(|&index| is_drawn(index))
+            .map(|index| self.nodes[index].round).max().unwrap_or(0);
+        let mut columns: Vec<Vec<usize>> = vec![Vec::new(); num_columns];
+        for (index, node) in self.nodes.iter().enumerate() {
+            if is_drawn(index) { columns[node.round - 1].push(index); }
+        }
+
+        // votes are drawn at the same scale in every column, so that the
+        // fullest column fits the chart
+        let max_nodes = columns.iter().map(Vec::len).max().unwrap_or(0);
+        let chart_height = f64::max(400.0, 2.0 * NODE_PADDING * max_nodes as f64);
+        let scale = columns.iter().filter_map(|column| {
+            let total_votes: u64 = column.iter().map(|&index| self.nodes[index].value).sum();
+            if total_votes == 0 { return None }
+            let padding = NODE_PADDING * column.len().saturating_sub(1) as f64;
+            Some((chart_height - padding) / total_votes as f64)
+        }).fold(f64::INFINITY, f64::min);
+        let scale = if scale.is_finite() { scale } else { 1.0 };
+
+        // (x, y, height) of every node
+        let mut layout: Vec<(f64, f64, f64)> = vec![(0.0, 0.0, 0.0); self.nodes.len()];
+        for (column_index, column) in columns.iter().enumerate() {
+            let x = MARGIN + COLUMN_GAP * column_index as f64;
+            let mut y = MARGIN + HEADER_HEIGHT;
+            for &index in column {
+                let height = f64::max(self.nodes[index].value as f64 * scale, 1.0);
+                layout[index] = (x, y, height);
+                y += height + NODE_PADDING;
+            }
+        }
+
+        // candidates keep the same color in every round: a candidate node
+        // continuing another candidate node from an earlier round takes
+        // its color, so colors are keyed by the candidate's first node
+        // rather than its (possibly shared) name
+        let mut continues: Vec<Option<usize>> = vec![None; self.nodes.len()];
+        for &(_, link) in &links {
+            let (source, target) = (&self.nodes[link.source], &self.nodes[link.target]);
+            if link.kind == SankeyLinkKinds::Continuing
+                && source.kind == SankeyNodeKinds::Candidate
+                && target.kind == SankeyNodeKinds::Candidate
+                && source.round < target.round {
+                continues[link.target] = Some(link.source);
+            }
+        }
+        let mut first_nodes: Vec<usize> = (0..self.nodes.len()).collect();
+        let mut candidate_colors: HashMap<usize, &str> = HashMap::new();
+        let mut colors: Vec<&str> = vec![""; self.nodes.len()];
+        for &index in columns.iter().flatten() {
+            let node = &self.nodes[index];
+            colors[index] = match node.kind {
+                SankeyNodeKinds::Candidate => {
+                    if let Some(source) = continues[index] {
+                        first_nodes[index] = first_nodes[source];
+                    }
+                    let num_colors = candidate_colors.len();
+                    candidate_colors.entry(first_nodes[index])
+                        .or_insert(PALETTE[num_colors % PALETTE.len()])
+                }
+                SankeyNodeKinds::Withhold => "#9e9e9e",
+                SankeyNodeKinds::Abstain => "#c7c7c7",
+                SankeyNodeKinds::Exhausted => "#616161"
+            };
+        }
+
+        // stack links leaving (and entering) each node in the order of
+        // the nodes at their other end, so that links don't cross
+        let mut link_offsets = vec![(0.0, 0.0); self.links.len()];
+        let mut link_order: Vec<usize> = links.iter().map(|&(index, _)| index).collect();
+        let mut node_offsets = vec![0.0; self.nodes.len()];
+        link_order.sort_by(|&link1, &link2| {
+            let (link1, link2) = (&self.links[link1], &self.links[link2]);
+            link1.source.cmp(&link2.source)
+                .then(layout[link1.target].1.total_cmp(&layout[link2.target].1))
+        });
+        for &index in &link_order {
+            let link = &self.links[index];
+            link_offsets[index].0 = node_offsets[link.source];
+            node_offsets[link.source] += link.value as f64 * scale;
+        }
+        let mut node_offsets = vec![0.0; self.nodes.len()];
+        link_order.sort_by(|&link1, &link2| {
+            let (link1, link2) = (&self.links[link1], &self.links[link2]);
+            link1.target.cmp(&link2.target)
+                .then(layout[link1.source].1.total_cmp(&layout[link2.source].1))
+        });
+        for &index in &link_order {
+            let link = &self.links[index];
+            link_offsets[index].1 = node_offsets[link.target];
+            node_offsets[link.target] += link.value as f64 * scale;
+        }
+
+        let width = 2.0 * MARGIN + COLUMN_GAP * num_columns.saturating_sub(1) as f64
+            + NODE_WIDTH + LABEL_WIDTH;
+        let height = 2.0 * MARGIN + HEADER_HEIGHT + chart_height;
+        let mut svg = String::new();
+        let mut line = |text: String| {
+            svg.push_str(&text);
+            svg.push('\n');
+        };
+        line(format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{:.0}\" \
+            height=\"{:.0}\" viewBox=\"0 0 {:.0} {:.0}\" \
+            font-family=\"sans-serif\" font-size=\"12\">",
+            width, height, width, height
+        ));
+        for column_index in 0..num_columns {
+            line(format!(
+                "<text x=\"{:.1}\" y=\"{:.1}\" font-weight=\"bold\">Round {}</text>",
+                MARGIN + COLUMN_GAP * column_index as f64, MARGIN + 12.0,
+                column_index + 1
+            ));
+        }
+
+        for &(index, link) in &links {
+            let (source_x, source_y, _) = layout[link.source];
+            let (target_x, target_y, _) = layout[link.target];
+            let x0 = source_x + NODE_WIDTH;
+            let x1 = target_x;
+            let middle_x = (x0 + x1) / 2.0;
+            let y0 = source_y + link_offsets[index].0;
+            let y1 = target_y + link_offsets[index].1;
+            let thickness = link.value as f64 * scale;
+            line(format!(
+                "<path d=\"M{:.1},{:.1} C{:.1},{:.1} {:.1},{:.1} {:.1},{:.1} \
+                L{:.1},{:.1} C{:.1},{:.1} {:.1},{:.1} {:.1},{:.1} Z\" \
+                fill=\"{}\" fill-opacity=\"0.4\"><title>{} \u{2192} {}: {}</title></path>",
+                x0, y0, middle_x, y0, middle_x, y1, x1, y1,
+                x1, y1 + thickness, middle_x, y1 + thickness,
+                middle_x, y0 + thickness, x0, y0 + thickness,
+                colors[link.source], escape_html(&self.nodes[link.source].name),
+                escape_html(&self.nodes[link.target].name), link.value
+            ));
+        }
+
+        for &index in columns.iter().flatten() {
+            let node = &self.nodes[index];
+            let (x, y, height) = layout[index];
+            let name = escape_html(&node.name);
+            line(format!(
+                "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" \
+                fill=\"{}\"><title>{}: {}</title></rect>",
+                x, y, NODE_WIDTH, height, colors[index], name, node.value
+            ));
+            line(format!(
+                "<text x=\"{:.1}\" y=\"{:.1}\" dominant-baseline=\"middle\">{}: {}</text>",
+                x + NODE_WIDTH + 6.0, y + height / 2.0, name, node.value
+            ));
+        }
+        line("</svg>".to_string());
+        svg
+    }
+}
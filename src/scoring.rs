@@ -0,0 +1,132 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use num_bigint::BigInt;
+use num_traits::Zero;
+pub use num_rational::BigRational;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use crate::CandidateId;
+
+/*
+positional scores are computed from how many ballots rank each
+candidate at each position (rank counts), using exact rational
+arithmetic so that candidates with equal scores are always tied
+*/
+
+pub(crate) fn add_rank_count(
+    rank_counts: &mut Vec<u64>, ranking: usize, num_votes: u64
+) {
+    if rank_counts.len() <= ranking {
+        rank_counts.resize(ranking + 1, 0);
+    }
+    rank_counts[ranking] += num_votes;
+}
+
+pub(crate) fn merge_rank_counts(rank_counts: &mut Vec<u64>, other: &[u64]) {
+    for (ranking, num_votes) in other.iter().enumerate() {
+        add_rank_count(rank_counts, ranking, *num_votes);
+    }
+}
+
+// how a candidate was ranked across all ballots
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CandidateRankCounts {
+    // number of ballots ranking the candidate at each position,
+    // starting from first choices
+    pub ranked: Vec<u64>,
+    // number of ballots that don't rank the candidate at all
+    pub unranked: u64
+}
+
+impl CandidateRankCounts {
+    pub fn num_ranked(&self) -> u64 {
+        self.ranked.iter().sum()
+    }
+
+    pub fn ranked_within(&self, num_rankings: usize) -> u64 {
+        // ballots ranking the candidate among their top num_rankings choices
+        self.ranked.iter().take(num_rankings).sum()
+    }
+}
+
+pub(crate) fn build_rank_histogram<C: CandidateId>(
+    rank_counts: &HashMap<C, Vec<u64>>, candidates: &HashSet<C>,
+    num_votes: u64
+) -> BTreeMap<C, CandidateRankCounts> {
+    // every candidate gets a count for every position that any
+    // ballot has, so the histogram can be read as a table
+    let num_rankings = rank_counts.values().map(Vec::len).max().unwrap_or(0);
+    candidates.iter().map(|candidate| {
+        let mut ranked = rank_counts.get(candidate).cloned().unwrap_or_default();
+        ranked.resize(num_rankings, 0);
+        let unranked = num_votes - ranked.iter().sum::<u64>();
+        (candidate.clone(), CandidateRankCounts { ranked, unranked })
+    }).collect()
+}
+
+pub fn dowdall_weight(ranking: usize) -> BigRational {
+    // the nth ranked choice (counting from 0) is worth 1 / (n + 1)
+    BigRational::new(BigInt::from(1), BigInt::from(ranking + 1))
+}
+
+pub fn positional_score<F>(rank_counts: &[u64], weight: F) -> BigRational
+where F: Fn(usize) -> BigRational {
+    /*
+    sums the weight of each ranking (counting from 0) over all
+    the ballots that rank a candidate at that position
+    */
+    let mut score = BigRational::zero();
+    for (ranking, num_votes) in rank_counts.iter().enumerate() {
+        if *num_votes == 0 { continue }
+        score += weight(ranking) * BigInt::from(*num_votes);
+    }
+    score
+}
+
+pub fn dowdall_score(rank_counts: &[u64]) -> BigRational {
+    positional_score(rank_counts, dowdall_weight)
+}
+
+pub(crate) fn positional_scores<C: CandidateId, F>(
+    rank_counts: &HashMap<C, Vec<u64>>, candidates: &HashSet<C>, weight: F
+) -> BTreeMap<C, BigRational>
+where F: Fn(usize) -> BigRational {
+    // candidates that no ballot ranks score zero
+    candidates.iter().map(|candidate| {
+        let rank_counts = rank_counts.get(candidate)
+            .map_or(&[][..], Vec::as_slice);
+        (candidate.clone(), positional_score(rank_counts, &weight))
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dowdall_score_is_exact() {
+        // 1/3 summed three times is exactly 1, unlike with floats
+        let third_place_votes = dowdall_score(&[0, 0, 3]);
+        assert_eq!(third_place_votes, dowdall_score(&[1]));
+
+        let mixed_votes = dowdall_score(&[2, 3, 6]);
+        let expected_score = BigRational::new(
+            BigInt::from(11), BigInt::from(2)
+        );
+        assert_eq!(mixed_votes, expected_score);
+    }
+
+    #[test]
+    fn test_rank_histogram_pads_rankings() {
+        let rank_counts = HashMap::from([(1, vec![2, 1]), (2, vec![1, 0, 1])]);
+        let candidates = HashSet::from([1, 2, 3]);
+        let histogram = build_rank_histogram(&rank_counts, &candidates, 4);
+
+        assert_eq!(histogram[&1].ranked, vec![2, 1, 0]);
+        assert_eq!(histogram[&1].unranked, 1);
+        assert_eq!(histogram[&2].ranked_within(2), 1);
+        assert_eq!(histogram[&3], CandidateRankCounts {
+            ranked: vec![0, 0, 0], unranked: 4
+        });
+    }
+}
@@ -0,0 +1,125 @@
+use std::collections::{HashMap, HashSet};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::de::Error;
+use crate::{EliminationStrategies, GenericRankedChoiceVoteTrie, GenericTrieNode};
+use crate::tabulation::count_rankings;
+use crate::vote::{
+    CandidateId, GenericRankedVote, GenericVoteValues, SpecialVotes, VoteErrors
+};
+
+// special votes go over the wire as their integer values (see to_int)
+impl Serialize for SpecialVotes {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i32(self.to_int())
+    }
+}
+
+impl<'de> Deserialize<'de> for SpecialVotes {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw_value = i32::deserialize(deserializer)?;
+        SpecialVotes::from_int(raw_value).map_err(D::Error::custom)
+    }
+}
+
+// unvalidated ranked vote, checked by GenericRankedVote::new when deserializing
+#[derive(Deserialize)]
+pub(crate) struct RankedVoteData<C> {
+    rankings: Vec<C>,
+    special_vote: Option<SpecialVotes>
+}
+
+impl<C: CandidateId> TryFrom<RankedVoteData<C>> for GenericRankedVote<C> {
+    type Error = VoteErrors;
+
+    fn try_from(data: RankedVoteData<C>) -> Result<Self, Self::Error> {
+        GenericRankedVote::new(data.rankings, data.special_vote)
+    }
+}
+
+// unvalidated trie, checked the same way as tries read by read_from
+#[derive(Deserialize)]
+#[serde(bound(deserialize = "C: CandidateId + Deserialize<'de>"))]
+pub(crate) struct TrieData<C> {
+    root: GenericTrieNode<C>,
+    rank_counts: HashMap<C, Vec<u64>>,
+    elimination_strategy: EliminationStrategies,
+    unique_candidates: HashSet<C>,
+    declared_candidates: HashSet<C>,
+    excluded_candidates: HashSet<C>
+}
+
+fn has_consistent_votes<C>(node: &GenericTrieNode<C>) -> bool {
+    // every child holds votes, and no more than its parent between them
+    let mut child_votes: u64 = 0;
+    for child in node.children.values() {
+        if child.num_votes == 0 || !has_consistent_votes(child) {
+            return false;
+        }
+        match child_votes.checked_add(child.num_votes) {
+            Some(total_votes) => child_votes = total_votes,
+            None => return false
+        }
+    }
+    child_votes <= node.num_votes
+}
+
+impl<C: CandidateId> TryFrom<TrieData<C>> for GenericRankedChoiceVoteTrie<C> {
+    type Error = String;
+
+    fn try_from(data: TrieData<C>) -> Result<Self, Self::Error> {
+        if !has_consistent_votes(&data.root) {
+            return Err(
+                "child node has no votes or more votes than its parent".to_string()
+            );
+        }
+        let mut rcv = GenericRankedChoiceVoteTrie::new();
+        rcv.root = data.root;
+        rcv.elimination_strategy = data.elimination_strategy;
+        rcv.declared_candidates = data.declared_candidates;
+        rcv.excluded_candidates = data.excluded_candidates;
+
+        let rank_counts = count_rankings(&rcv);
+        let is_listed = |candidate: &C| data.unique_candidates.contains(candidate);
+        if rank_counts != data.rank_counts || !rank_counts.keys().all(is_listed) {
+            return Err("rank counts don't match the ballots in the trie".to_string());
+        }
+        rcv.rank_counts = rank_counts;
+        rcv.unique_candidates = data.unique_candidates;
+        Ok(rcv)
+    }
+}
+
+pub(crate) mod children {
+    /*
+    trie node children are keyed by vote values, which aren't valid
+    map keys in formats like JSON, so they're written as a list of
+    (vote value, child node) pairs sorted by vote value instead
+    */
+    use super::*;
+
+    type Children<C> = HashMap<GenericVoteValues<C>, GenericTrieNode<C>>;
+
+    pub fn serialize<C, S>(
+        children: &Children<C>, serializer: S
+    ) -> Result<S::Ok, S::Error>
+    where C: CandidateId + Serialize, S: Serializer {
+        let mut child_pairs: Vec<_> = children.iter().collect();
+        child_pairs.sort_by_key(|(vote_value, _)| *vote_value);
+        child_pairs.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, C, D>(
+        deserializer: D
+    ) -> Result<Children<C>, D::Error>
+    where C: CandidateId + Deserialize<'de>, D: Deserializer<'de> {
+        let child_pairs: Vec<(GenericVoteValues<C>, GenericTrieNode<C>)> =
+            Vec::deserialize(deserializer)?;
+        let mut children = Children::with_capacity(child_pairs.len());
+        for (vote_value, child) in child_pairs {
+            if children.insert(vote_value, child).is_some() {
+                return Err(D::Error::custom("node has duplicate children"));
+            }
+        }
+        Ok(children)
+    }
+}
@@ -0,0 +1,788 @@
+use std::borrow::Cow;
+use std::cmp::{min, Ordering};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use itertools::iproduct;
+use petgraph::Direction;
+use petgraph::graph::{DiGraph, NodeIndex};
+use crate::{
+    is_graph_acyclic, is_graph_weakly_connected, CandidateId,
+    ElectionResult, ElectionRound, EliminationReasons, EliminationStrategies,
+    InactiveBallots, PairPreferences, SpoilerEffect, VoteFlows
+};
+use crate::scoring::{add_rank_count, dowdall_score, BigRational};
+use crate::traversal::TrieTraversal;
+use crate::vote::{GenericVoteValues, SpecialVotes};
+
+pub(crate) type RankedPairsMap<C> = HashMap<(C, C), u64>;
+// number of ballots ranking each candidate at each position
+pub(crate) type RankCountsMap<C> = HashMap<C, Vec<u64>>;
+
+struct VoteTransfer<N, C> {
+    next_candidate: C,
+    next_node: N,
+    num_votes: u64
+}
+
+struct VoteTransferChanges<N, C> {
+    withhold_votes: u64, abstain_votes: u64,
+    // (next candidate, next node, num votes to transfer to next candidate)
+    vote_transfers: Vec<VoteTransfer<N, C>>
+}
+
+fn add_vote_flows<N, C: CandidateId>(
+    vote_flows: &mut VoteFlows<C>, node_votes: u64,
+    transfer_changes: &VoteTransferChanges<N, C>
+) {
+    // votes that don't move on to a candidate, withhold or abstain
+    // are on ballots that have run out of rankings
+    let transferred_votes: u64 = transfer_changes.vote_transfers
+        .iter().map(|transfer| transfer.num_votes).sum();
+    vote_flows.exhausted_votes += node_votes - transferred_votes
+        - transfer_changes.withhold_votes - transfer_changes.abstain_votes;
+    vote_flows.withhold_votes += transfer_changes.withhold_votes;
+    vote_flows.abstain_votes += transfer_changes.abstain_votes;
+    for transfer in &transfer_changes.vote_transfers {
+        *vote_flows.transfers
+            .entry(transfer.next_candidate.clone()).or_insert(0) +=
+            transfer.num_votes;
+    }
+}
+
+fn transfer_next_votes<'a, C: CandidateId, T: TrieTraversal<C>>(
+    trie: &'a T, node: T::Node<'a>, eliminated_candidates: &HashSet<C>
+) -> VoteTransferChanges<T::Node<'a>, C> {
+    let mut transfer_changes = VoteTransferChanges {
+        withhold_votes: 0, abstain_votes: 0,
+        vote_transfers: Default::default(),
+    };
+    collect_next_votes(
+        trie, node, eliminated_candidates, &mut transfer_changes
+    );
+    transfer_changes
+}
+
+fn collect_next_votes<'a, C: CandidateId, T: TrieTraversal<C>>(
+    trie: &'a T, node: T::Node<'a>, eliminated_candidates: &HashSet<C>,
+    transfer_changes: &mut VoteTransferChanges<T::Node<'a>, C>
+) {
+    trie.visit_children(node, |next_vote_value, next_node| {
+        let next_votes = trie.node_votes(next_node);
+        match next_vote_value {
+            GenericVoteValues::SpecialVote(special_vote) => {
+                match special_vote {
+                    SpecialVotes::WITHHOLD => {
+                        transfer_changes.withhold_votes += next_votes;
+                    },
+                    SpecialVotes::ABSTAIN => {
+                        transfer_changes.abstain_votes += next_votes;
+                    }
+                }
+            },
+            GenericVoteValues::Candidate(next_candidate) => {
+                // skip over candidates that are already out of the
+                // running and move on to the next ranked choice
+                if eliminated_candidates.contains(next_candidate) {
+                    collect_next_votes(
+                        trie, next_node, eliminated_candidates,
+                        transfer_changes
+                    );
+                    return;
+                }
+
+                transfer_changes.vote_transfers.push(VoteTransfer{
+                    next_candidate: next_candidate.clone(), next_node,
+                    num_votes: next_votes
+                });
+            }
+        }
+    });
+}
+
+pub(crate) fn count_rankings<C: CandidateId, T: TrieTraversal<C>>(
+    trie: &T
+) -> RankCountsMap<C> {
+    // rank counts of the ballots as cast, without any exclusions
+    let mut rank_counts = HashMap::new();
+    build_rank_counts(
+        trie, trie.root_node(), 0, &mut rank_counts, &HashSet::new()
+    );
+    rank_counts
+}
+
+fn build_rank_counts<'a, C: CandidateId, T: TrieTraversal<C>>(
+    trie: &'a T, node: T::Node<'a>, ranking: usize,
+    rank_counts: &mut RankCountsMap<C>,
+    excluded_candidates: &HashSet<C>
+) {
+    /*
+    recounts how many ballots rank each candidate at each position,
+    with excluded candidates removed from every ballot (i.e. later
+    choices move up a rank)
+    */
+    trie.visit_children(node, |vote_value, child| {
+        let candidate = match vote_value {
+            GenericVoteValues::SpecialVote(_) => { return }
+            GenericVoteValues::Candidate(candidate) => { candidate }
+        };
+
+        if excluded_candidates.contains(candidate) {
+            build_rank_counts(
+                trie, child, ranking, rank_counts, excluded_candidates
+            );
+            return;
+        }
+
+        add_rank_count(
+            rank_counts.entry(candidate.clone()).or_default(),
+            ranking, trie.node_votes(child)
+        );
+        build_rank_counts(
+            trie, child, ranking + 1, rank_counts, excluded_candidates
+        );
+    });
+}
+
+pub(crate) fn build_child_ranked_pairs_map<'a, C, T>(
+    trie: &'a T, vote_value: &GenericVoteValues<C>, child: T::Node<'a>,
+    search_path: &mut Vec<C>,
+    ranked_pairs_map: &mut RankedPairsMap<C>,
+    unique_candidates: &HashSet<C>,
+    excluded_candidates: &HashSet<C>
+) where C: CandidateId, T: TrieTraversal<C> {
+    let candidate = match vote_value {
+        GenericVoteValues::SpecialVote(_) => { return }
+        GenericVoteValues::Candidate(candidate) => { candidate }
+    };
+
+    // excluded candidates are treated as if they were never ranked
+    if excluded_candidates.contains(candidate) {
+        build_ranked_pairs_map(
+            trie, child, search_path, ranked_pairs_map,
+            unique_candidates, excluded_candidates
+        );
+        return;
+    }
+
+    let child_votes = trie.node_votes(child);
+    for preferable_candidate in search_path.iter() {
+        let ranked_pair =
+            (preferable_candidate.clone(), candidate.clone());
+        let pairwise_votes =
+            ranked_pairs_map.entry(ranked_pair).or_insert(0);
+        *pairwise_votes += child_votes;
+    }
+
+    search_path.push(candidate.clone());
+    build_ranked_pairs_map(
+        trie, child, search_path, ranked_pairs_map,
+        unique_candidates, excluded_candidates
+    );
+    search_path.pop();
+}
+
+fn build_ranked_pairs_map<'a, C: CandidateId, T: TrieTraversal<C>>(
+    trie: &'a T, node: T::Node<'a>, search_path: &mut Vec<C>,
+    ranked_pairs_map: &mut RankedPairsMap<C>,
+    unique_candidates: &HashSet<C>,
+    excluded_candidates: &HashSet<C>
+) {
+    // number of votes that terminate at node
+    let mut terminating_votes: u64 = trie.node_votes(node);
+
+    trie.visit_children(node, |vote_value, child| {
+        let child_votes = trie.node_votes(child);
+        assert!(terminating_votes >= child_votes);
+        terminating_votes -= child_votes;
+        build_child_ranked_pairs_map(
+            trie, vote_value, child, search_path, ranked_pairs_map,
+            unique_candidates, excluded_candidates
+        );
+    });
+
+    if terminating_votes > 0 {
+        // candidates who weren't explicitly listed in current vote path
+        let search_path: &Vec<C> = search_path;
+        let mut unspecified_candidates = unique_candidates.clone();
+        for candidate in search_path {
+            unspecified_candidates.remove(candidate);
+        }
+
+        let pairs = iproduct!(search_path, &unspecified_candidates);
+        for (preferable_candidate, candidate) in pairs {
+            let ranked_pair =
+                (preferable_candidate.clone(), candidate.clone());
+            let pairwise_votes =
+                ranked_pairs_map.entry(ranked_pair).or_insert(0);
+            *pairwise_votes += terminating_votes;
+        }
+    }
+}
+
+fn find_condorcet_ranked_pairs_weakest<C: CandidateId>(
+    candidate_vote_counts: &HashMap<C, u64>,
+    ranked_pairs_map: &RankedPairsMap<C>,
+    lowest_vote_candidates: Vec<C>
+) -> Vec<C> {
+    let mut vote_counts: Vec<u64> =
+        candidate_vote_counts.values().cloned().collect();
+    vote_counts.sort();
+
+    // get the second-lowest number of effective votes, or the lowest
+    // number of votes if the second-lowest number of effective votes
+    // is not available
+    let vote_threshold = match vote_counts.get(1) {
+        Some(second_lowest_votes) => { *second_lowest_votes }
+        None => {
+            match vote_counts.first() {
+                Some(lowest_votes) => { *lowest_votes }
+                None => { return vec![] }
+            }
+        }
+    };
+
+    // find candidates with less than or equal to the
+    // second-lowest number of effective votes
+    let mut weak_candidates: Vec<C> = Vec::new();
+    for (candidate, num_votes) in candidate_vote_counts {
+        if *num_votes <= vote_threshold {
+            weak_candidates.push(candidate.clone());
+        }
+    }
+
+    let pairs_result = find_ranked_pairs_weakest(
+        weak_candidates, ranked_pairs_map
+    );
+
+    if !pairs_result.1 {
+        lowest_vote_candidates
+    } else {
+        pairs_result.0
+    }
+}
+
+fn find_ranked_pairs_weakest<C: CandidateId>(
+    candidates: Vec<C>, ranked_pairs_map: &RankedPairsMap<C>
+) -> (Vec<C>, bool) {
+    /*
+    Finds the candidates that perform the worst in pairwise
+    head-to-head comparison.
+    Returns the worst performing candidates, and whether it was possible
+    to construct a preference graph
+    */
+    let mut graph = DiGraph::<C, u64>::new();
+    let mut node_map = HashMap::<C, NodeIndex>::new();
+
+    /*
+    Determines whether candidate1 is preferred over candidate2 overall,
+    or vice versa, or there is no net preference between the two.
+    Also returns the net number of votes along said overall preference
+    */
+    let get_preference = |
+        candidate1: &C, candidate2: &C
+    | -> (PairPreferences, u64) {
+        let preferred_over_votes = ranked_pairs_map
+            .get(&(candidate1.clone(), candidate2.clone()))
+            .unwrap_or(&0);
+        let preferred_against_votes = ranked_pairs_map
+            .get(&(candidate2.clone(), candidate1.clone()))
+            .unwrap_or(&0);
+
+        match preferred_over_votes.cmp(preferred_against_votes) {
+            Ordering::Greater => {
+                let strength =
+                    preferred_over_votes - preferred_against_votes;
+                (PairPreferences::PreferredOver, strength)
+            }
+            Ordering::Equal => {
+                (PairPreferences::Inconclusive, 0)
+            }
+            Ordering::Less => {
+                let strength =
+                    preferred_against_votes - preferred_over_votes;
+                (PairPreferences::PreferredAgainst, strength)
+            }
+        }
+    };
+
+    fn get_or_create_node<C: CandidateId> (
+        graph: &mut DiGraph<C, u64>,
+        node_map: &mut HashMap<C, NodeIndex>,
+        candidate: &C
+    ) -> NodeIndex {
+        match node_map.get(candidate) {
+            Some(node) => { *node }
+            None => {
+                let node = graph.add_node(candidate.clone());
+                node_map.insert(candidate.clone(), node);
+                node
+            }
+        }
+    }
+
+    // initialize all the nodes in the graph
+    for candidate in &candidates {
+        get_or_create_node(&mut graph, &mut node_map, candidate);
+    }
+
+    // construct preference strength graph between candidates
+    for (candidate1, candidate2) in iproduct!(&candidates, &candidates) {
+        if candidate1 == candidate2 { continue }
+        let (preference, strength) =
+            get_preference(candidate1, candidate2);
+
+        match preference {
+            PairPreferences::PreferredAgainst => { continue }
+            PairPreferences::Inconclusive => { continue }
+            PairPreferences::PreferredOver => {}
+        }
+
+        assert!(preference == PairPreferences::PreferredOver);
+        let node1_idx =
+            get_or_create_node(&mut graph, &mut node_map, candidate1);
+        let node2_idx =
+            get_or_create_node(&mut graph, &mut node_map, candidate2);
+        if !graph.contains_edge(node1_idx, node2_idx) {
+            graph.add_edge(node1_idx, node2_idx, strength);
+        }
+    }
+
+    // unable to establish pecking order among candidates
+    if !(is_graph_acyclic(&graph) && is_graph_weakly_connected(&graph)) {
+        return (candidates.clone(), false);
+    }
+
+    let has_no_outgoing_edges = |&node: &NodeIndex| -> bool {
+        graph.neighbors_directed(node, Direction::Outgoing).count() == 0
+    };
+    let weakest_nodes: Vec<NodeIndex> = graph
+        .node_indices()
+        .filter(has_no_outgoing_edges)
+        .collect();
+
+    let weakest_candidates = weakest_nodes
+        .iter().map(|&index| graph[index].clone()).collect();
+    (weakest_candidates, true)
+}
+
+fn find_dowdall_weakest<C: CandidateId>(
+    candidates: Vec<C>, rank_counts: &RankCountsMap<C>
+) -> Vec<C> {
+    /*
+    returns the subset of candidates from the input candidates vector
+    that score the lowest according the dowdall scoring criteria
+    */
+    // declared candidates that no ballot ranks have no rank counts
+    let scores: Vec<BigRational> = candidates.iter().map(|candidate| {
+        dowdall_score(rank_counts.get(candidate).map_or(&[], Vec::as_slice))
+    }).collect();
+
+    let min_score = match scores.iter().min() {
+        Some(min_score) => min_score,
+        None => return Vec::new()
+    };
+    candidates.iter().zip(&scores)
+        .filter(|(_, score)| *score == min_score)
+        .map(|(candidate, _)| candidate.clone())
+        .collect()
+}
+
+// ballot trie together with the settings needed to tabulate it
+pub struct Tabulator<'a, C, T> {
+    pub trie: &'a T,
+    pub elimination_strategy: EliminationStrategies,
+    pub unique_candidates: &'a HashSet<C>,
+    pub excluded_candidates: &'a HashSet<C>,
+    // rank counts of the ballots as cast, if the trie keeps track
+    // of them (otherwise they are recounted from the trie when needed)
+    pub rank_counts: Option<&'a RankCountsMap<C>>
+}
+
+pub trait TabulationSource<C> {
+    /*
+    what traversal::TrieTabulation needs from each kind of trie: its
+    tabulator and settings, and how candidates map to the keys the
+    trie is tabulated with (the candidates themselves, or indexes into
+    a candidate table). This trait lives in a private module so that
+    it can't be implemented or called outside of this crate
+    */
+    type Key: CandidateId;
+    type Trie: TrieTraversal<Self::Key>;
+
+    fn tabulator(&self) -> Tabulator<'_, Self::Key, Self::Trie>;
+    fn elimination_strategy_mut(&mut self) -> &mut EliminationStrategies;
+    fn excluded_candidates_mut(&mut self) -> &mut HashSet<Self::Key>;
+    // None for candidates that the trie has no key for
+    fn to_key(&self, candidate: &C) -> Option<Self::Key>;
+    fn to_candidate(&self, key: &Self::Key) -> C;
+}
+
+impl<'a, C: CandidateId, T: TrieTraversal<C>> Tabulator<'a, C, T> {
+    pub fn tabulate(&self) -> ElectionResult<C> {
+        self.tabulate_excluding(self.excluded_candidates)
+    }
+
+    pub fn uses_ranked_pairs(&self) -> bool {
+        let strategy = self.elimination_strategy;
+        (strategy == EliminationStrategies::RankedPairs) ||
+        (strategy == EliminationStrategies::CondorcetRankedPairs)
+    }
+
+    pub fn contesting_candidates(
+        &self, excluded_candidates: &HashSet<C>
+    ) -> HashSet<C> {
+        self.unique_candidates
+            .difference(excluded_candidates).cloned().collect()
+    }
+
+    pub fn build_ranked_pairs_map(
+        &self, excluded_candidates: &HashSet<C>
+    ) -> RankedPairsMap<C> {
+        let mut ranked_pairs_map: RankedPairsMap<C> = HashMap::new();
+        build_ranked_pairs_map(
+            self.trie, self.trie.root_node(), &mut Vec::new(),
+            &mut ranked_pairs_map,
+            &self.contesting_candidates(excluded_candidates),
+            excluded_candidates
+        );
+        ranked_pairs_map
+    }
+
+    pub fn find_spoilers(&self) -> Vec<SpoilerEffect<C>> {
+        let original_winner = self.tabulate().winner;
+        let mut candidates: Vec<&C> = self.unique_candidates
+            .difference(self.excluded_candidates).collect();
+        candidates.sort();
+
+        let mut spoiler_effects: Vec<SpoilerEffect<C>> = Vec::new();
+        let mut excluded_candidates = self.excluded_candidates.clone();
+
+        for candidate in candidates {
+            if original_winner.as_ref() == Some(candidate) { continue }
+            excluded_candidates.insert(candidate.clone());
+            let winner = self.tabulate_excluding(&excluded_candidates).winner;
+            excluded_candidates.remove(candidate);
+
+            if winner != original_winner {
+                spoiler_effects.push(SpoilerEffect {
+                    spoiler: candidate.clone(),
+                    original_winner: original_winner.clone(),
+                    winner_without_spoiler: winner
+                });
+            }
+        }
+
+        spoiler_effects
+    }
+
+    pub fn tabulate_excluding(
+        &self, excluded_candidates: &HashSet<C>
+    ) -> ElectionResult<C> {
+        let ranked_pairs_map = match self.uses_ranked_pairs() {
+            true => self.build_ranked_pairs_map(excluded_candidates),
+            false => HashMap::new()
+        };
+        self.tabulate_with_ranked_pairs(excluded_candidates, ranked_pairs_map)
+    }
+
+    pub fn tabulate_with_ranked_pairs(
+        &self, excluded_candidates: &HashSet<C>,
+        ranked_pairs_map: RankedPairsMap<C>
+    ) -> ElectionResult<C> {
+        let mut rounds: Vec<ElectionRound<C>> = Vec::new();
+        let contesting_candidates = self.contesting_candidates(
+            excluded_candidates
+        );
+        let mut state = TabulationState::new(
+            self.trie, &contesting_candidates, excluded_candidates
+        );
+
+        // rank counts only need to be recounted if ballots
+        // have to be read with some candidates removed from them
+        let rank_counts: Cow<RankCountsMap<C>> = match self.rank_counts {
+            Some(rank_counts) if excluded_candidates.is_empty() => {
+                Cow::Borrowed(rank_counts)
+            }
+            _ => {
+                let mut rank_counts = HashMap::new();
+                if self.elimination_strategy ==
+                    EliminationStrategies::DowdallScoring
+                {
+                    build_rank_counts(
+                        self.trie, self.trie.root_node(), 0,
+                        &mut rank_counts, excluded_candidates
+                    );
+                }
+                Cow::Owned(rank_counts)
+            }
+        };
+
+        let winner = loop {
+            if state.candidate_vote_counts.is_empty() { break None; }
+            rounds.push(state.current_round());
+            let candidate_vote_counts = &state.candidate_vote_counts;
+            let effective_total_votes = state.effective_total_votes;
+
+            // total number of votes that go to candidates
+            let total_candidate_votes: u64 =
+                candidate_vote_counts.values().sum();
+            // impossible for any candidate to win as sum of
+            // candidate votes is under the total number of votes cast
+            if total_candidate_votes <= effective_total_votes / 2 {
+                break None;
+            }
+
+            let mut min_candidate_votes: u64 = u64::MAX;
+            let mut majority_candidate: Option<C> = None;
+            for (candidate, num_votes) in candidate_vote_counts {
+                min_candidate_votes = min(min_candidate_votes, *num_votes);
+                // some candidate has won a majority of the votes
+                if *num_votes > effective_total_votes / 2 {
+                    majority_candidate = Some(candidate.clone());
+                }
+            }
+            if majority_candidate.is_some() { break majority_candidate; }
+
+            // find candidates with the lowest number of effective votes
+            let mut lowest_vote_candidates: Vec<C> = Vec::new();
+            for (candidate, num_votes) in candidate_vote_counts {
+                if *num_votes == min_candidate_votes {
+                    lowest_vote_candidates.push(candidate.clone());
+                }
+            }
+
+            let num_lowest_vote_candidates = lowest_vote_candidates.len();
+
+            // further filter down candidates to eliminate using
+            // specified elimination strategy
+            let mut weakest_candidates = match self.elimination_strategy {
+                EliminationStrategies::EliminateAll => {
+                    lowest_vote_candidates
+                },
+                EliminationStrategies::DowdallScoring => {
+                    find_dowdall_weakest(lowest_vote_candidates, &rank_counts)
+                },
+                EliminationStrategies::RankedPairs => {
+                    find_ranked_pairs_weakest(
+                        lowest_vote_candidates, &ranked_pairs_map
+                    ).0
+                },
+                EliminationStrategies::CondorcetRankedPairs => {
+                    find_condorcet_ranked_pairs_weakest(
+                        candidate_vote_counts, &ranked_pairs_map,
+                        lowest_vote_candidates
+                    )
+                }
+            };
+
+            // no candidate can be eliminated, election is unable to progress
+            if weakest_candidates.is_empty() { break None; }
+            weakest_candidates.sort();
+            // the strategy only decided the elimination if it didn't
+            // simply eliminate every candidate with the fewest votes
+            let eliminated_all_lowest =
+                weakest_candidates.len() == num_lowest_vote_candidates &&
+                weakest_candidates.iter().all(|candidate| {
+                    candidate_vote_counts[candidate] == min_candidate_votes
+                });
+            let elimination_reason = match eliminated_all_lowest {
+                true => EliminationReasons::FewestVotes,
+                false => match self.elimination_strategy {
+                    EliminationStrategies::EliminateAll => {
+                        EliminationReasons::FewestVotes
+                    },
+                    EliminationStrategies::DowdallScoring => {
+                        EliminationReasons::LowestDowdallScore
+                    },
+                    EliminationStrategies::RankedPairs => {
+                        EliminationReasons::LostRankedPairs
+                    },
+                    EliminationStrategies::CondorcetRankedPairs => {
+                        EliminationReasons::LostCondorcetRankedPairs
+                    }
+                }
+            };
+
+            // 0 vote transfers will be done, election is unable to progress
+            let is_stuck = !state.ranks_next_candidates(&weakest_candidates) &&
+                weakest_candidates.iter().any(|candidate| {
+                    candidate_vote_counts[candidate] > 0
+                });
+            let transfers = state.eliminate(&weakest_candidates);
+            let round = rounds.last_mut()
+                .expect("current round must have been recorded");
+            round.eliminated = weakest_candidates;
+            round.elimination_reason = Some(elimination_reason);
+            round.transfers = transfers;
+
+            if is_stuck { break None; }
+        };
+
+        let mut excluded_candidates: Vec<C> =
+            excluded_candidates.iter().cloned().collect();
+        excluded_candidates.sort();
+        ElectionResult { winner, rounds, excluded_candidates }
+    }
+
+    pub fn replay_tabulation(
+        &self, election_result: &ElectionResult<C>
+    ) -> ElectionResult<C> {
+        let excluded_candidates: HashSet<C> =
+            election_result.excluded_candidates.iter().cloned().collect();
+        let contesting_candidates: HashSet<C> = match
+            election_result.rounds.first()
+        {
+            Some(first_round) => first_round.tallies.keys().cloned().collect(),
+            None => self.contesting_candidates(&excluded_candidates)
+        };
+
+        let mut state = TabulationState::new(
+            self.trie, &contesting_candidates, &excluded_candidates
+        );
+        let mut rounds: Vec<ElectionRound<C>> = Vec::new();
+
+        for election_round in &election_result.rounds {
+            let mut round = state.current_round();
+            round.eliminated = election_round.eliminated.clone();
+            round.elimination_reason = election_round.elimination_reason;
+            round.transfers = state.eliminate(&round.eliminated);
+            rounds.push(round);
+        }
+
+        ElectionResult {
+            winner: election_result.winner.clone(),
+            rounds,
+            excluded_candidates: election_result.excluded_candidates.clone()
+        }
+    }
+}
+
+// vote tallies and trie positions of ballots partway through tabulation
+pub(crate) struct TabulationState<'a, C, T: TrieTraversal<C> + 'a> {
+    trie: &'a T,
+    pub candidate_vote_counts: HashMap<C, u64>,
+    // trie nodes of the ballots currently counted towards each candidate
+    frontier_nodes: HashMap<C, Vec<T::Node<'a>>>,
+    eliminated_candidates: HashSet<C>,
+    // total number of voters (who have no abstained from vote)
+    pub effective_total_votes: u64,
+    pub inactive_ballots: InactiveBallots
+}
+
+impl<'a, C: CandidateId, T: TrieTraversal<C>> TabulationState<'a, C, T> {
+    pub fn new(
+        trie: &'a T, contesting_candidates: &HashSet<C>,
+        excluded_candidates: &HashSet<C>
+    ) -> Self {
+        // every known candidate starts with a tally of zero so that
+        // candidates without first choice votes are still eliminated
+        let root = trie.root_node();
+        let mut state = TabulationState {
+            trie,
+            candidate_vote_counts: contesting_candidates.iter()
+                .map(|candidate| (candidate.clone(), 0)).collect(),
+            frontier_nodes: HashMap::new(),
+            eliminated_candidates: excluded_candidates.clone(),
+            effective_total_votes: trie.node_votes(root),
+            inactive_ballots: InactiveBallots::default()
+        };
+
+        // allocate each ballot to its first choice that isn't excluded
+        let first_choices = transfer_next_votes(
+            trie, root, &state.eliminated_candidates
+        );
+        let mut first_choice_flows = VoteFlows::default();
+        add_vote_flows(
+            &mut first_choice_flows, trie.node_votes(root), &first_choices
+        );
+        state.inactive_ballots.add_flows(&first_choice_flows);
+        state.effective_total_votes -= first_choices.abstain_votes;
+        state.apply_transfers(first_choices.vote_transfers);
+        state
+    }
+
+    pub fn current_round(&self) -> ElectionRound<C> {
+        ElectionRound {
+            tallies: self.candidate_vote_counts.iter().map(
+                |(candidate, num_votes)| (candidate.clone(), *num_votes)
+            ).collect(),
+            eliminated: Vec::new(),
+            elimination_reason: None,
+            transfers: BTreeMap::new(),
+            inactive_ballots: self.inactive_ballots
+        }
+    }
+
+    pub fn ranks_next_candidates(&self, candidates: &[C]) -> bool {
+        // whether any ballot held by candidates ranks another candidate
+        // (eliminated or not) right after them
+        let mut ranks_next_candidate = false;
+        for candidate in candidates {
+            let candidate_nodes = self.frontier_nodes.get(candidate);
+            for node in candidate_nodes.into_iter().flatten() {
+                self.trie.visit_children(*node, |vote_value, _| {
+                    if let GenericVoteValues::Candidate(_) = vote_value {
+                        ranks_next_candidate = true;
+                    }
+                });
+            }
+        }
+        ranks_next_candidate
+    }
+
+    pub fn eliminate(
+        &mut self, weakest_candidates: &[C]
+    ) -> BTreeMap<C, VoteFlows<C>> {
+        // moves the ballots of the weakest candidates on to their next
+        // remaining choices, returning where each candidate's ballots went
+        self.eliminated_candidates.extend(weakest_candidates.iter().cloned());
+
+        // find all candidates, nodes, and vote counts to transfer to
+        let mut all_vote_transfers: Vec<VoteTransfer<T::Node<'a>, C>> =
+            Vec::new();
+        let mut all_vote_flows = BTreeMap::new();
+
+        for weakest_candidate in weakest_candidates {
+            let candidate_nodes = self.frontier_nodes
+                .remove(weakest_candidate).unwrap_or_default();
+            let mut vote_flows = VoteFlows::default();
+
+            for node in candidate_nodes {
+                let transfer_result = transfer_next_votes(
+                    self.trie, node, &self.eliminated_candidates
+                );
+                add_vote_flows(
+                    &mut vote_flows, self.trie.node_votes(node),
+                    &transfer_result
+                );
+                all_vote_transfers.extend(transfer_result.vote_transfers);
+            }
+
+            self.effective_total_votes -= vote_flows.abstain_votes;
+            self.inactive_ballots.add_flows(&vote_flows);
+            self.candidate_vote_counts.remove(weakest_candidate);
+            all_vote_flows.insert(weakest_candidate.clone(), vote_flows);
+        }
+
+        self.apply_transfers(all_vote_transfers);
+        all_vote_flows
+    }
+
+    fn apply_transfers(
+        &mut self, vote_transfers: Vec<VoteTransfer<T::Node<'a>, C>>
+    ) {
+        // conduct vote transfers to next candidates and trie nodes
+        for vote_transfer in vote_transfers {
+            let next_candidate = vote_transfer.next_candidate;
+            let vote_allocation = vote_transfer.num_votes;
+            assert!(vote_allocation > 0);
+
+            let next_candidate_votes = self.candidate_vote_counts
+                .entry(next_candidate.clone()).or_insert(0);
+            let next_candidate_nodes = self.frontier_nodes
+                .entry(next_candidate).or_default();
+
+            *next_candidate_votes += vote_allocation;
+            next_candidate_nodes.push(vote_transfer.next_node);
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use crate::{CandidateId, ElectionResult, EliminationStrategies, SpoilerEffect};
+use crate::tabulation::TabulationSource;
+use crate::vote::GenericVoteValues;
+
+pub trait TrieTraversal<C> {
+    /*
+    read-only access to the nodes of a ballot trie, which is all that
+    tabulation needs. This lets the same tabulation code run over tries
+    that are laid out differently in memory (e.g. hash map children,
+    a flat arena of nodes, or a memory-mapped file)
+    */
+    type Node<'a>: Copy where Self: 'a;
+
+    fn root_node(&self) -> Self::Node<'_>;
+
+    // number of ballots whose path passes through (or ends at) node
+    fn node_votes<'a>(&'a self, node: Self::Node<'a>) -> u64;
+
+    // calls visit with the vote value and node of each child of node
+    fn visit_children<'a, F>(&'a self, node: Self::Node<'a>, visit: F)
+    where F: FnMut(&GenericVoteValues<C>, Self::Node<'a>);
+}
+
+pub trait TrieTabulation<C: CandidateId>: TabulationSource<C> {
+    /*
+    tabulation settings and methods shared by every kind of trie
+    (GenericRankedChoiceVoteTrie, GenericArenaVoteTrie,
+    GenericFrozenTrie and GenericMappedTrie), all implemented once
+    on top of TrieTraversal
+    */
+    fn set_elimination_strategy(&mut self, strategy: EliminationStrategies) {
+        *self.elimination_strategy_mut() = strategy;
+    }
+
+    fn exclude_candidates(&mut self, candidates: &[C]) {
+        /*
+        marks candidates (e.g. candidates who withdrew after ballots
+        were printed) as eliminated before the first round of tabulation.
+        Excluded candidates are skipped over on every ballot, and ballots
+        that rank them are counted towards their next ranked choice instead
+        */
+        for candidate in candidates {
+            // frozen tries have no ballots ranking unknown candidates
+            if let Some(key) = self.to_key(candidate) {
+                self.excluded_candidates_mut().insert(key);
+            }
+        }
+    }
+
+    fn clear_excluded_candidates(&mut self) {
+        self.excluded_candidates_mut().clear();
+    }
+
+    fn determine_winner(&self) -> Option<C> {
+        self.tabulate().winner
+    }
+
+    fn tabulate(&self) -> ElectionResult<C> {
+        /*
+        runs the election round by round, returning the winner (if any)
+        together with the tallies and eliminations of every round
+        */
+        self.tabulator().tabulate()
+            .map_candidates(|key| Some(self.to_candidate(key)))
+    }
+
+    fn find_spoilers(&self) -> Vec<SpoilerEffect<C>> {
+        /*
+        reruns the election on the same trie with each candidate excluded
+        in turn, and reports every candidate whose absence would have
+        changed the winner. The original winner is not considered a
+        spoiler of their own election
+        */
+        let to_candidate = |key: Self::Key| self.to_candidate(&key);
+        self.tabulator().find_spoilers().into_iter()
+            .map(|spoiler_effect| SpoilerEffect {
+                spoiler: to_candidate(spoiler_effect.spoiler),
+                original_winner: spoiler_effect.original_winner
+                    .map(to_candidate),
+                winner_without_spoiler: spoiler_effect.winner_without_spoiler
+                    .map(to_candidate)
+            }).collect()
+    }
+
+    fn replay_tabulation(
+        &self, election_result: &ElectionResult<C>
+    ) -> ElectionResult<C> {
+        /*
+        replays the eliminations of an election that was tabulated
+        elsewhere (e.g. across all precincts) on the ballots in this
+        trie, and returns how these ballots were distributed among
+        the remaining candidates in each round of that election.
+        Frozen tries leave out candidates that none of their ballots rank
+        */
+        let key_result = election_result
+            .map_candidates(|candidate| self.to_key(candidate));
+        let mut replayed_result = self.tabulator()
+            .replay_tabulation(&key_result)
+            .map_candidates(|key| Some(self.to_candidate(key)));
+        replayed_result.winner = election_result.winner.clone();
+        replayed_result
+    }
+}
+
+impl<C: CandidateId, T: TabulationSource<C>> TrieTabulation<C> for T {}
@@ -1,20 +1,31 @@
 use std::collections::HashSet;
 use std::fmt;
-use petgraph::matrix_graph::Zero;
+use std::hash::Hash;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
-#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+// any type that can be used to identify a candidate in a ranked vote
+pub trait CandidateId: Eq + Hash + Clone + Ord {}
+
+impl<T: Eq + Hash + Clone + Ord> CandidateId for T {}
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, PartialOrd, Ord)]
 pub enum SpecialVotes {
     WITHHOLD,
     ABSTAIN
 }
 
-#[derive(Clone, Copy, Eq, PartialEq, Hash)]
-pub enum VoteValues {
-    Candidate(u32),
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum GenericVoteValues<C> {
+    Candidate(C),
     SpecialVote(SpecialVotes)
 }
 
+pub type VoteValues = GenericVoteValues<u32>;
+
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum VoteErrors {
     InvalidCastToCandidate,
     InvalidCastToSpecialVote,
@@ -79,12 +90,37 @@ impl SpecialVotes {
     }
 }
 
-pub struct RankedVote {
-    rankings: Vec<u32>,
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(
+    try_from = "crate::serde_support::RankedVoteData<C>",
+    bound(deserialize = "C: CandidateId + Deserialize<'de>")
+))]
+pub struct GenericRankedVote<C> {
+    rankings: Vec<C>,
     special_vote: Option<SpecialVotes>
 }
 
-impl RankedVote {
+pub type RankedVote = GenericRankedVote<u32>;
+
+impl<C: CandidateId> GenericRankedVote<C> {
+    pub fn new(
+        rankings: Vec<C>, special_vote: Option<SpecialVotes>
+    ) -> Result<GenericRankedVote<C>, VoteErrors> {
+        if special_vote.is_none() && rankings.is_empty() {
+            return Err(VoteErrors::VoteIsEmpty)
+        }
+
+        let mut unique_candidates = HashSet::new();
+        for candidate in &rankings {
+            if !unique_candidates.insert(candidate) {
+                return Err(VoteErrors::DuplicateVotes);
+            }
+        }
+
+        Ok(GenericRankedVote { rankings, special_vote })
+    }
+
     pub fn len(&self) -> usize {
         let mut length = self.rankings.len();
         if self.special_vote.is_some() { length += 1; }
@@ -95,23 +131,41 @@ impl RankedVote {
         self.len() == 0
     }
 
-    pub fn get(&self, index: usize) -> Result<VoteValues, VoteErrors> {
+    pub fn rankings(&self) -> &[C] {
+        &self.rankings
+    }
+
+    pub fn special_vote(&self) -> Option<SpecialVotes> {
+        self.special_vote
+    }
+
+    pub fn get(&self, index: usize) -> Result<GenericVoteValues<C>, VoteErrors> {
         let rankings_length = self.rankings.len();
         let special_vote_option = self.special_vote;
 
         if index == rankings_length {
             if let Some(special_vote) = special_vote_option {
-                return Ok(VoteValues::SpecialVote(special_vote))
+                return Ok(GenericVoteValues::SpecialVote(special_vote))
             }
         }
 
         let read_result = self.rankings.get(index);
         match read_result {
             None => { Err(VoteErrors::ReadOutOfBounds) }
-            Some(choice) => { Ok(VoteValues::Candidate(*choice)) }
+            Some(choice) => {
+                Ok(GenericVoteValues::Candidate(choice.clone()))
+            }
         }
     }
 
+    pub fn from_candidates(
+        candidates: &[C]
+    ) -> Result<GenericRankedVote<C>, VoteErrors> {
+        Self::new(candidates.to_vec(), None)
+    }
+}
+
+impl RankedVote {
     pub fn from_vectors(
         raw_votes: &Vec<Vec<i32>>
     ) -> Result<Vec<RankedVote>, VoteErrors> {
@@ -130,19 +184,10 @@ impl RankedVote {
         Ok(votes)
     }
 
-    pub fn from_candidates(
-        candidates: &[u32]
-    ) -> Result<RankedVote, VoteErrors> {
-        Self::from_vector(
-            &candidates.iter().map(|x| *x as i32).collect()
-        )
-    }
-
     #[allow(clippy::ptr_arg)]
     pub fn from_vector(
         raw_ranked_vote: &Vec<i32>
     ) -> Result<RankedVote, VoteErrors> {
-        // println!("INSERT {:?}", raw_rankings);
         let mut candidates: Vec<u32> = Vec::new();
         let mut special_vote_value: Option<SpecialVotes> = None;
         let mut unique_values = HashSet::new();
@@ -182,14 +227,7 @@ impl RankedVote {
             }
         }
 
-        if special_vote_value.is_none() && candidates.is_empty() {
-            return Err(VoteErrors::VoteIsEmpty)
-        }
-
-        // println!("INSERT_END {:?}", raw_rankings);
-        Ok(RankedVote {
-            rankings: candidates, special_vote: special_vote_value
-        })
+        RankedVote::new(candidates, special_vote_value)
     }
 
     pub fn to_vector(&self) -> Vec<i64> {
@@ -204,18 +242,18 @@ impl RankedVote {
     }
 }
 
-pub struct VoteStructIterator<'a> {
-    rankings_iter: std::slice::Iter<'a, u32>,
+pub struct VoteStructIterator<'a, C> {
+    rankings_iter: std::slice::Iter<'a, C>,
     special_vote: Option<&'a SpecialVotes>,
 }
 
-impl<'a> Iterator for VoteStructIterator<'a> {
-    type Item = VoteValues;
+impl<C: Clone> Iterator for VoteStructIterator<'_, C> {
+    type Item = GenericVoteValues<C>;
 
     fn next(&mut self) -> Option<Self::Item> {
         // create iterator for normal rankings
         let ranking = self.rankings_iter.next().map(
-            |&r| VoteValues::Candidate(r)
+            |r| GenericVoteValues::Candidate(r.clone())
         );
         if ranking.is_some() {
             return ranking;
@@ -225,7 +263,7 @@ impl<'a> Iterator for VoteStructIterator<'a> {
         match self.special_vote {
             None => None,
             Some(special_vote) => {
-                let item = Some(VoteValues::SpecialVote(*special_vote));
+                let item = Some(GenericVoteValues::SpecialVote(*special_vote));
                 self.special_vote = None;
                 item
             }
@@ -233,9 +271,9 @@ impl<'a> Iterator for VoteStructIterator<'a> {
     }
 }
 
-impl RankedVote {
+impl<C> GenericRankedVote<C> {
     // Method to create an iterator over the vote values
-    pub fn iter(&self) -> VoteStructIterator {
+    pub fn iter(&self) -> VoteStructIterator<'_, C> {
         VoteStructIterator {
             rankings_iter: self.rankings.iter(),
             special_vote: self.special_vote.as_ref(),
@@ -298,4 +336,21 @@ mod tests {
             raw_ranked_vote.iter().map(|&x| x as i64).collect::<Vec<i64>>()
         )
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_generic_candidates() {
+        let vote = GenericRankedVote::new(
+            vec!["a".to_string(), "b".to_string()],
+            Some(SpecialVotes::WITHHOLD)
+        ).unwrap();
+        assert_eq!(vote.len(), 3);
+        assert!(vote.get(2).unwrap() == GenericVoteValues::SpecialVote(
+            SpecialVotes::WITHHOLD
+        ));
+
+        let duplicate_result = GenericRankedVote::from_candidates(
+            &["a".to_string(), "a".to_string()]
+        );
+        assert!(duplicate_result.is_err());
+    }
+}
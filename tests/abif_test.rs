@@ -0,0 +1,90 @@
+use std::collections::BTreeMap;
+use trie_rcv::{GenericRankedChoiceVoteTrie, TrieTabulation};
+use trie_rcv::formats::abif::{read_abif, write_abif, AbifErrors};
+use trie_rcv::vote::GenericRankedVote;
+
+const ABIF_FILE: &str = "{\"title\": \"Best fruit\"}
+# candidates
+=apple:[Red Apple]
+=banana:[Banana]
+=[cherry pie]:[Cherry Pie #1]
+=durian
+
+27:apple>banana>[cherry pie] # most common ballot
+12: banana = [cherry pie] > apple
+5:[cherry pie]>banana
+3:banana
+";
+
+fn to_vote(rankings: &[&str]) -> GenericRankedVote<String> {
+    let candidates: Vec<String> =
+        rankings.iter().map(|candidate| candidate.to_string()).collect();
+    GenericRankedVote::from_candidates(&candidates).unwrap()
+}
+
+#[test]
+fn test_read_abif() {
+    let election = read_abif(ABIF_FILE.as_bytes()).unwrap();
+    assert_eq!(election.metadata, vec!["{\"title\": \"Best fruit\"}"]);
+    assert_eq!(election.candidate_names["apple"], "Red Apple");
+    assert_eq!(election.candidate_names["cherry pie"], "Cherry Pie #1");
+    assert_eq!(election.candidate_names["durian"], "durian");
+
+    // the tied ballots are cut short and abstain
+    assert_eq!(election.num_truncated_votes, 12);
+    assert_eq!(election.trie.get_num_votes(), 47);
+    let ballots: Vec<_> = election.trie.ballots().collect();
+    assert_eq!(ballots.len(), 4);
+    assert!(ballots.contains(
+        &(to_vote(&["apple", "banana", "cherry pie"]), 27)
+    ));
+    assert_eq!(election.trie.determine_winner(), Some("apple".to_string()));
+}
+
+#[test]
+fn test_abif_round_trip_stays_aggregated() {
+    let mut rcv = GenericRankedChoiceVoteTrie::new();
+    for _ in 0..4 {
+        rcv.insert_vote(to_vote(&["x", "y z"]));
+    }
+    rcv.insert_vote(to_vote(&["y z"]));
+    let candidate_names = BTreeMap::from([
+        ("x".to_string(), "Candidate X".to_string())
+    ]);
+
+    let mut buffer = Vec::new();
+    write_abif(&mut buffer, &rcv, &candidate_names).unwrap();
+    let written = String::from_utf8(buffer).unwrap();
+    assert_eq!(written, "=x:[Candidate X]\n=[y z]:[y z]\n4:x>[y z]\n1:[y z]\n");
+
+    let election = read_abif(written.as_bytes()).unwrap();
+    assert_eq!(election.candidate_names, BTreeMap::from([
+        ("x".to_string(), "Candidate X".to_string()),
+        ("y z".to_string(), "y z".to_string())
+    ]));
+    let ballots: Vec<_> = election.trie.ballots().collect();
+    assert_eq!(ballots, rcv.ballots().collect::<Vec<_>>());
+}
+
+#[test]
+fn test_abif_errors_report_lines() {
+    let rated = "=A\n=B\n3:A/5>B/2\n";
+    let error = read_abif(rated.as_bytes()).unwrap_err();
+    assert!(matches!(error, AbifErrors::UnsupportedScores { line: 3 }));
+
+    let missing_count = "A>B\n";
+    let error = read_abif(missing_count.as_bytes()).unwrap_err();
+    assert_eq!(error.to_string(), "line 1: expected \"count:ballot\"");
+
+    let duplicate = "\n2:A>B>A\n";
+    let error = read_abif(duplicate.as_bytes()).unwrap_err();
+    assert_eq!(error.line(), Some(2));
+
+    let zero_count = "=A\n0:A\n";
+    let error = read_abif(zero_count.as_bytes()).unwrap_err();
+    assert!(matches!(error, AbifErrors::ZeroCount { line: 2 }));
+
+    let unclosed = "2:[A>B\n";
+    let error = read_abif(unclosed.as_bytes()).unwrap_err();
+    assert!(matches!(error, AbifErrors::InvalidCandidate { line: 1 }));
+}
@@ -0,0 +1,136 @@
+use trie_rcv::{EliminationStrategies, RankedChoiceVoteTrie, TrieTabulation};
+use trie_rcv::arena::{ArenaVoteTrie, GenericArenaVoteTrie};
+use trie_rcv::prefix::PrefixPattern;
+use trie_rcv::rctab::RctabRules;
+use trie_rcv::vote::{GenericRankedVote, RankedVote};
+
+const STRATEGIES: [EliminationStrategies; 4] = [
+    EliminationStrategies::EliminateAll,
+    EliminationStrategies::DowdallScoring,
+    EliminationStrategies::RankedPairs,
+    EliminationStrategies::CondorcetRankedPairs
+];
+
+fn build_votes() -> Vec<RankedVote> {
+    let vote_rankings = vec![
+        vec![1, 2, 3], vec![1, 3, 2], vec![2, 3, 1], vec![2, 1],
+        vec![3, 2, 1], vec![3, 1], vec![4, 3, 2, 1], vec![4, 2],
+        vec![2, 3], vec![1, -1], vec![3, 2, -2], vec![5, 4, 3]
+    ];
+    let mut votes = Vec::new();
+    for repeats in 1..=10 {
+        for (index, rankings) in vote_rankings.iter().enumerate() {
+            if (repeats + index) % 4 == 0 { continue }
+            votes.push(RankedVote::from_vector(rankings).unwrap());
+        }
+    }
+    votes
+}
+
+#[test]
+fn test_arena_trie_matches_trie() {
+    for strategy in STRATEGIES {
+        let mut rcv = RankedChoiceVoteTrie::new();
+        rcv.set_elimination_strategy(strategy);
+        rcv.exclude_candidates(&[5]);
+        rcv.insert_votes(build_votes());
+
+        let mut arena_rcv = ArenaVoteTrie::new();
+        arena_rcv.set_elimination_strategy(strategy);
+        arena_rcv.exclude_candidates(&[5]);
+        arena_rcv.insert_votes(build_votes());
+
+        assert_eq!(arena_rcv.get_num_votes(), rcv.get_num_votes());
+        let election_result = rcv.tabulate();
+        assert_eq!(arena_rcv.tabulate(), election_result);
+        assert_eq!(arena_rcv.find_spoilers(), rcv.find_spoilers());
+        assert_eq!(
+            arena_rcv.replay_tabulation(&election_result),
+            rcv.replay_tabulation(&election_result)
+        );
+        assert_eq!(
+            arena_rcv.run_election(build_votes()), rcv.determine_winner()
+        );
+    }
+}
+
+#[test]
+fn test_arena_trie_merge() {
+    let votes = build_votes();
+    let (first_votes, second_votes) = votes.split_at(votes.len() / 2);
+
+    let mut arena_rcv = ArenaVoteTrie::new();
+    arena_rcv.insert_votes(votes.clone());
+    let mut merged_rcv = ArenaVoteTrie::new();
+    merged_rcv.insert_votes(first_votes.to_vec());
+    let mut second_rcv = ArenaVoteTrie::new();
+    second_rcv.insert_votes(second_votes.to_vec());
+    merged_rcv.merge(second_rcv);
+
+    // shared ballot prefixes are merged into the same nodes
+    assert_eq!(merged_rcv.get_num_nodes(), arena_rcv.get_num_nodes());
+    assert_eq!(merged_rcv.get_num_votes(), arena_rcv.get_num_votes());
+    assert_eq!(merged_rcv.tabulate(), arena_rcv.tabulate());
+}
+
+#[test]
+fn test_arena_trie_can_replace_trie() {
+    let weighted_vote = RankedVote::from_vector(&vec![2, 4]).unwrap();
+    let mut rcv = RankedChoiceVoteTrie::new();
+    rcv.insert_votes(build_votes());
+    rcv.insert_weighted_vote(weighted_vote.clone(), 5);
+    let mut arena_rcv: ArenaVoteTrie = build_votes().into_iter()
+        .map(|vote| {
+            let mut vote_rcv = ArenaVoteTrie::new();
+            vote_rcv.insert_vote(vote);
+            vote_rcv
+        }).sum();
+    arena_rcv.insert_weighted_vote(weighted_vote.clone(), 5);
+    arena_rcv.insert_weighted_vote(RankedVote::from_vector(&vec![3]).unwrap(), 0);
+
+    assert_eq!(
+        arena_rcv.ballots().collect::<Vec<_>>(),
+        rcv.ballots().collect::<Vec<_>>()
+    );
+    assert_eq!(arena_rcv.dowdall_scores(), rcv.dowdall_scores());
+    assert_eq!(arena_rcv.count_prefix(&[2, 4]), rcv.count_prefix(&[2, 4]));
+    let pattern = [PrefixPattern::AnyCandidate, PrefixPattern::Candidate(3)];
+    assert_eq!(
+        arena_rcv.next_choices_matching(&pattern),
+        rcv.next_choices_matching(&pattern)
+    );
+    let node_path = arena_rcv.search_nodes(weighted_vote).unwrap();
+    assert_eq!(node_path[2].get_num_votes(), 5);
+    assert_eq!(
+        arena_rcv.tabulate_rctab(&RctabRules::default()),
+        rcv.tabulate_rctab(&RctabRules::default())
+    );
+    assert_eq!(arena_rcv.freeze().tabulate(), rcv.freeze().tabulate());
+
+    // both tries read and write the same binary format
+    let mut bytes = Vec::new();
+    arena_rcv.write_to(&mut bytes).unwrap();
+    let mut trie_bytes = Vec::new();
+    rcv.write_to(&mut trie_bytes).unwrap();
+    assert_eq!(bytes, trie_bytes);
+    let read_rcv = ArenaVoteTrie::read_from(&mut bytes.as_slice()).unwrap();
+    assert_eq!(read_rcv.get_num_nodes(), arena_rcv.get_num_nodes());
+    assert_eq!(read_rcv.tabulate(), rcv.tabulate());
+}
+
+#[test]
+fn test_arena_trie_string_candidates() {
+    let votes = vec![
+        vec!["alice", "bob"], vec!["bob", "alice"],
+        vec!["carol", "bob"], vec!["bob"], vec!["alice", "carol"]
+    ];
+    let mut arena_rcv = GenericArenaVoteTrie::new();
+    for vote in votes {
+        let candidates: Vec<String> =
+            vote.into_iter().map(String::from).collect();
+        arena_rcv.insert_vote(
+            GenericRankedVote::from_candidates(&candidates).unwrap()
+        );
+    }
+    assert_eq!(arena_rcv.determine_winner(), Some("bob".to_string()));
+}
@@ -0,0 +1,52 @@
+use trie_rcv::{EliminationStrategies, RankedChoiceVoteTrie, TrieTabulation};
+use trie_rcv::vote::{RankedVote, SpecialVotes};
+
+const WITHOLD_VOTE_VAL: i32 = SpecialVotes::WITHHOLD.to_int();
+const ABSTAIN_VOTE_VAL: i32 = SpecialVotes::ABSTAIN.to_int();
+
+#[test]
+fn test_ballots_lists_distinct_ballots() {
+    let raw_votes = vec![
+        vec![2, 1], vec![1], vec![1, 3], vec![1, 3], vec![2, 1],
+        vec![1, WITHOLD_VOTE_VAL], vec![ABSTAIN_VOTE_VAL], vec![1, 3]
+    ];
+    let mut rcv = RankedChoiceVoteTrie::new();
+    rcv.insert_votes(RankedVote::from_vectors(&raw_votes).unwrap());
+
+    let ballots: Vec<(Vec<i64>, u64)> = rcv.ballots()
+        .map(|(vote, count)| (vote.to_vector(), count))
+        .collect();
+    // children are visited in sorted order, with special votes last
+    assert_eq!(ballots, vec![
+        (vec![1], 1),
+        (vec![1, 3], 3),
+        (vec![1, WITHOLD_VOTE_VAL as i64], 1),
+        (vec![2, 1], 2),
+        (vec![ABSTAIN_VOTE_VAL as i64], 1)
+    ]);
+}
+
+#[test]
+fn test_ballots_rebuild_trie() {
+    let raw_votes = vec![
+        vec![1, 2, 3], vec![1, 2], vec![1, 2, 3], vec![3, 2, 1],
+        vec![2, 3], vec![3, 1, ABSTAIN_VOTE_VAL], vec![2], vec![3, 2]
+    ];
+    let mut rcv = RankedChoiceVoteTrie::new();
+    rcv.set_elimination_strategy(EliminationStrategies::DowdallScoring);
+    rcv.insert_votes(RankedVote::from_vectors(&raw_votes).unwrap());
+
+    let mut rebuilt_rcv = RankedChoiceVoteTrie::new();
+    rebuilt_rcv.set_elimination_strategy(EliminationStrategies::DowdallScoring);
+    for (vote, count) in rcv.ballots() {
+        for _ in 0..count {
+            rebuilt_rcv.insert_vote(vote.clone());
+        }
+    }
+
+    let total_votes: u64 = rcv.ballots().map(|(_, count)| count).sum();
+    assert_eq!(total_votes, rcv.get_num_votes());
+    assert_eq!(rebuilt_rcv.get_num_votes(), rcv.get_num_votes());
+    assert_eq!(rebuilt_rcv.rank_histogram(), rcv.rank_histogram());
+    assert_eq!(rebuilt_rcv.tabulate(), rcv.tabulate());
+}
@@ -0,0 +1,91 @@
+use trie_rcv::{RankedChoiceVoteTrie, TrieTabulation};
+use trie_rcv::formats::blt::{read_blt, write_blt, BltElection, BltErrors};
+use trie_rcv::vote::{RankedVote, SpecialVotes};
+
+const BLT_FILE: &str = "4 1
+-4
+3 1 2 0
+(b2) 2 2 - 3 0
+
+1 3 1 4 0
+1 0
+0
+\"Alice\"
+\"Bob\"
+\"Carol\"
+\"Dave\"
+\"Club President\"
+";
+
+#[test]
+fn test_read_blt() {
+    let election = read_blt(BLT_FILE.as_bytes()).unwrap();
+    assert_eq!(election.title, "Club President");
+    assert_eq!(election.num_seats, 1);
+    assert_eq!(election.candidate_names, vec!["Alice", "Bob", "Carol", "Dave"]);
+    assert_eq!(election.withdrawn_candidates, vec![4]);
+    assert_eq!(election.ballots, vec![
+        (RankedVote::from_vector(&vec![1, 2]).unwrap(), 3),
+        (RankedVote::from_vector(&vec![2, 3]).unwrap(), 2),
+        (RankedVote::from_vector(&vec![3, 1, 4]).unwrap(), 1),
+        (RankedVote::new(vec![], Some(SpecialVotes::ABSTAIN)).unwrap(), 1)
+    ]);
+
+    let mut rcv = RankedChoiceVoteTrie::new();
+    rcv.declare_candidates(&[1, 2, 3, 4]);
+    rcv.exclude_candidates(&[4]);
+    rcv.insert_votes(election.to_votes());
+    assert_eq!(election.to_trie().tabulate(), rcv.tabulate());
+    assert_eq!(election.to_trie().get_num_votes(), 7);
+}
+
+#[test]
+fn test_blt_round_trip() {
+    let election = read_blt(BLT_FILE.as_bytes()).unwrap();
+    let exported = BltElection::from_trie(
+        &election.to_trie(), &election.title, election.num_seats,
+        election.candidate_names.clone()
+    ).unwrap();
+
+    let mut buffer = Vec::new();
+    write_blt(&mut buffer, &exported).unwrap();
+    let reloaded = read_blt(buffer.as_slice()).unwrap();
+    assert_eq!(reloaded, exported);
+    assert_eq!(reloaded.to_trie().tabulate(), election.to_trie().tabulate());
+}
+
+#[test]
+fn test_blt_errors_report_lines() {
+    let missing_terminator = "2 1\n1 1 2\n0\n\"A\"\n\"B\"\n\"T\"\n";
+    let error = read_blt(missing_terminator.as_bytes()).unwrap_err();
+    assert!(matches!(error, BltErrors::MissingBallotTerminator { line: 2 }));
+
+    let unknown_candidate = "2 1\n\n1 1 2 0\n1 3 0\n0\n\"A\"\n\"B\"\n\"T\"\n";
+    let error = read_blt(unknown_candidate.as_bytes()).unwrap_err();
+    assert_eq!(error.line(), Some(4));
+    assert_eq!(error.to_string(), "line 4: invalid candidate 3");
+
+    let tied_rankings = "2 1\n1 1=2 0\n0\n\"A\"\n\"B\"\n\"T\"\n";
+    let error = read_blt(tied_rankings.as_bytes()).unwrap_err();
+    assert!(matches!(error, BltErrors::TiedRankings { line: 2 }));
+
+    let missing_title = "2 1\n1 1 2 0\n0\n\"A\"\n\"B\"\n";
+    let error = read_blt(missing_title.as_bytes()).unwrap_err();
+    assert!(matches!(error, BltErrors::UnexpectedEndOfFile));
+}
+
+#[test]
+fn test_blt_zero_weight_ballots() {
+    let zero_weight = "2 1\n0 1 2 0\n3 2 0\n0\n\"A\"\n\"B\"\n\"T\"\n";
+    let error = read_blt(zero_weight.as_bytes()).unwrap_err();
+    assert!(matches!(error, BltErrors::ZeroWeight { line: 2 }));
+    assert_eq!(error.line(), Some(2));
+
+    // ballots without weight are left out of the trie when tabulating
+    let mut election = read_blt("2 1\n3 2 0\n0\n\"A\"\n\"B\"\n\"T\"\n".as_bytes()).unwrap();
+    election.ballots.insert(0, (RankedVote::from_vector(&vec![1, 2]).unwrap(), 0));
+    let rcv = election.to_trie();
+    assert_eq!(rcv.get_num_votes(), 3);
+    assert_eq!(rcv.tabulate().winner, Some(2));
+    assert_eq!(rcv.ballots().count(), 1);
+}
@@ -0,0 +1,47 @@
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+fn run_cli(args: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_trie_rcv"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // the CLI may exit (e.g. on bad arguments) before reading its input
+    let _ = child.stdin.take().unwrap().write_all(input.as_bytes());
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn test_cli_exit_codes() {
+    let output = run_cli(&[], "1, 2\n1\n# comment\n\n2 1\n3 2\n");
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("Round 1\n  1: 2\n"));
+    assert!(stdout.ends_with("Winner: 1\n"));
+
+    let output = run_cli(&["--strategy", "eliminate-all"], "1\n2\n");
+    assert_eq!(output.status.code(), Some(2));
+
+    // withheld votes count towards the majority but go to nobody
+    let output = run_cli(&["-"], "1 -1\n2 -1\n-1\n-1\n");
+    assert_eq!(output.status.code(), Some(3));
+
+    let output = run_cli(&[], "1 1\n");
+    assert_eq!(output.status.code(), Some(1));
+    let output = run_cli(&["--strategy", "borda"], "1\n");
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn test_cli_reads_blt() {
+    let blt_file = "3 1\n-2\n4 1 2 0\n3 2 1 0\n2 3 2 0\n0\n\"Alice\"\n\"Bob\"\n\"Carol\"\n\"Mayor\"\n";
+    let output = run_cli(&["--format", "blt"], blt_file);
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    // Bob is withdrawn, so his ballots count for their next choice
+    assert!(stdout.starts_with("Round 1\n  Alice: 7\n  Carol: 2\n"));
+    assert!(stdout.ends_with("Winner: Alice\n"));
+}
@@ -0,0 +1,81 @@
+#![cfg(feature = "csv")]
+use trie_rcv::{RankedChoiceVoteTrie, TrieTabulation};
+use trie_rcv::formats::csv::{
+    read_csv_ballots, CsvErrors, CsvOptions, CsvRowErrors, RankColumns
+};
+use trie_rcv::rctab::ExhaustionReasons;
+use trie_rcv::registry::CandidateRegistry;
+use trie_rcv::vote::RankedVote;
+
+const CSV_FILE: &str = "Ballot ID,Precinct,Choice 1,Choice 2,Choice 3
+1,North,Alice,Bob,
+2,North,Bob,overvote,Alice
+3,South,,Carol,Carol
+4,South,Dave,\"Alice\",Bob
+5,South,Bob,Alice,Carol
+6,North,undervote,,
+";
+
+#[test]
+fn test_read_csv_ballots() {
+    let mut registry = CandidateRegistry::from_names(&["Alice", "Bob", "Carol"]);
+    let mut rcv = RankedChoiceVoteTrie::new();
+    let log = read_csv_ballots(
+        CSV_FILE.as_bytes(), &CsvOptions::default(), &mut registry, &mut rcv
+    ).unwrap();
+
+    assert!(log.errors.is_empty());
+    assert_eq!(log.num_ballots, 6);
+    assert_eq!(log.num_overvotes, 1);
+    assert_eq!(log.exhausted_ballots, vec![
+        (RankedVote::from_vector(&vec![2]).unwrap(), ExhaustionReasons::Overvote)
+    ]);
+    // write-in candidates are registered as they come up
+    assert_eq!(registry.get_id("Dave"), Some(4));
+    assert_eq!(registry.get_name(2), Some("Bob"));
+
+    let ballots: Vec<(Vec<i64>, u64)> = rcv.ballots()
+        .map(|(vote, count)| (vote.to_vector(), count))
+        .collect();
+    assert_eq!(ballots, vec![
+        (vec![1, 2], 1), (vec![2], 1), (vec![2, 1, 3], 1), (vec![3], 1),
+        (vec![4, 1, 2], 1), (vec![-2], 1)
+    ]);
+    // Carol's only ballot is eliminated with nowhere to go
+    assert_eq!(rcv.determine_winner(), None);
+}
+
+#[test]
+fn test_csv_row_errors_are_logged() {
+    let csv_file = "first;second\nAlice;Bob\nZed;Alice\nBob;Alice\n";
+    let options = CsvOptions {
+        rank_columns: RankColumns::Named(
+            vec!["first".to_string(), "second".to_string()]
+        ),
+        delimiter: b';',
+        register_new_candidates: false,
+        ..CsvOptions::default()
+    };
+    let mut registry = CandidateRegistry::from_names(&["Alice", "Bob"]);
+    let mut rcv = RankedChoiceVoteTrie::new();
+    let log = read_csv_ballots(
+        csv_file.as_bytes(), &options, &mut registry, &mut rcv
+    ).unwrap();
+
+    assert_eq!(log.num_ballots, 2);
+    assert_eq!(log.errors.len(), 1);
+    assert_eq!(log.errors[0].line, 3);
+    assert!(matches!(
+        &log.errors[0].error, CsvRowErrors::UnknownCandidate(name) if name == "Zed"
+    ));
+    assert_eq!(rcv.get_num_votes(), 2);
+
+    let options = CsvOptions {
+        rank_columns: RankColumns::Named(vec!["Rank 1".to_string()]),
+        ..CsvOptions::default()
+    };
+    let error = read_csv_ballots(
+        CSV_FILE.as_bytes(), &options, &mut registry, &mut rcv
+    ).unwrap_err();
+    assert!(matches!(error, CsvErrors::MissingColumn(column) if column == "Rank 1"));
+}
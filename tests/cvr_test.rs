@@ -0,0 +1,115 @@
+#![cfg(feature = "cvr")]
+use serde_json::{json, Value};
+use trie_rcv::formats::cvr::{read_cvr_report, CvrErrors};
+use trie_rcv::rctab::ExhaustionReasons;
+use trie_rcv::vote::{GenericRankedVote, SpecialVotes, VoteErrors};
+
+fn ranked_cvr(cvr_id: &str, marks: &[(&str, u32)]) -> Value {
+    let selections: Vec<Value> = marks.iter().map(|(selection_id, rank)| json!({
+        "ContestSelectionId": selection_id,
+        "SelectionPosition": [{
+            "Rank": rank, "HasIndication": "yes", "IsAllocable": "yes",
+            "NumberVotes": 1
+        }]
+    })).collect();
+    json!({
+        "@id": cvr_id,
+        "CurrentSnapshotId": format!("{}-current", cvr_id),
+        "CVRSnapshot": [
+            {"@id": format!("{}-original", cvr_id), "CVRContest": []},
+            {"@id": format!("{}-current", cvr_id), "CVRContest": [{
+                "ContestId": "mayor", "CVRContestSelection": selections
+            }]}
+        ]
+    })
+}
+
+fn build_report(cvrs: Vec<Value>) -> String {
+    json!({
+        "@type": "CVR.CastVoteRecordReport",
+        "Election": [{
+            "@id": "election-2024",
+            "Candidate": [
+                {"@id": "alice", "Name": "Alice"},
+                {"@id": "bob", "Name": "Bob"},
+                {"@id": "carol", "Name": "Carol"}
+            ],
+            "Contest": [{
+                "@id": "mayor", "@type": "CVR.CandidateContest",
+                "ContestSelection": [
+                    {"@id": "cs-alice", "CandidateIds": ["alice"]},
+                    {"@id": "cs-bob", "CandidateIds": ["bob"]},
+                    {"@id": "cs-carol", "CandidateIds": ["carol"]},
+                    {"@id": "cs-write-in", "IsWriteIn": true}
+                ]
+            }]
+        }],
+        "CVR": cvrs
+    }).to_string()
+}
+
+fn to_vote(rankings: &[&str]) -> GenericRankedVote<String> {
+    let candidates: Vec<String> =
+        rankings.iter().map(|candidate| candidate.to_string()).collect();
+    GenericRankedVote::from_candidates(&candidates).unwrap()
+}
+
+#[test]
+fn test_read_cvr_report() {
+    let report = build_report(vec![
+        ranked_cvr("1", &[("cs-alice", 1), ("cs-bob", 2)]),
+        // skipped rank 2 and a repeated ranking of carol
+        ranked_cvr("2", &[("cs-carol", 1), ("cs-carol", 2), ("cs-bob", 3)]),
+        // overvote at rank 2 ends the ballot
+        ranked_cvr("3", &[("cs-bob", 1), ("cs-alice", 2), ("cs-carol", 2)]),
+        ranked_cvr("4", &[("cs-write-in", 1), ("cs-alice", 2)]),
+        ranked_cvr("5", &[]),
+        json!({"@id": "6", "CVRSnapshot": [{"@id": "s", "CVRContest": []}]})
+    ]);
+
+    let contest = read_cvr_report(report.as_bytes(), "mayor").unwrap();
+    assert_eq!(contest.candidate_names["alice"], "Alice");
+    assert_eq!(contest.candidate_names["cs-write-in"], "Write-in");
+    assert_eq!(contest.num_overvotes, 1);
+    assert_eq!(contest.exhausted_ballots, vec![
+        (to_vote(&["bob"]), ExhaustionReasons::Overvote)
+    ]);
+    assert_eq!(contest.votes, vec![
+        to_vote(&["alice", "bob"]),
+        to_vote(&["carol", "bob"]),
+        to_vote(&["bob"]),
+        to_vote(&["cs-write-in", "alice"]),
+        GenericRankedVote::new(vec![], Some(SpecialVotes::ABSTAIN)).unwrap()
+    ]);
+
+    let rcv = contest.to_trie();
+    assert_eq!(rcv.get_num_votes(), 5);
+    assert_eq!(rcv.rank_histogram().len(), 4);
+}
+
+#[test]
+fn test_cvr_errors() {
+    let report = build_report(vec![ranked_cvr("1", &[("cs-alice", 1)])]);
+    let error = read_cvr_report(report.as_bytes(), "governor").unwrap_err();
+    assert!(matches!(error, CvrErrors::UnknownContest(_)));
+
+    let report = build_report(vec![ranked_cvr("7", &[("cs-dave", 1)])]);
+    let error = read_cvr_report(report.as_bytes(), "mayor").unwrap_err();
+    assert_eq!(
+        error.to_string(), "CVR 7: unknown contest selection \"cs-dave\""
+    );
+
+    // the contest has 4 selections, so ranks past 4 can't be marked
+    let report = build_report(vec![ranked_cvr("8", &[("cs-bob", 1_000_000)])]);
+    let error = read_cvr_report(report.as_bytes(), "mayor").unwrap_err();
+    assert!(matches!(error, CvrErrors::InvalidRank { rank: 1_000_000, .. }));
+    let report = build_report(vec![ranked_cvr("9", &[("cs-bob", 0)])]);
+    let error = read_cvr_report(report.as_bytes(), "mayor").unwrap_err();
+    assert_eq!(error.to_string(), "CVR 9: rank 0 is out of range");
+
+    let error = read_cvr_report("{\"CVR\": 5}".as_bytes(), "mayor").unwrap_err();
+    assert!(matches!(error, CvrErrors::Json(_)));
+
+    let error = CvrErrors::from(VoteErrors::DuplicateVotes);
+    assert!(matches!(error, CvrErrors::InvalidVote(VoteErrors::DuplicateVotes)));
+}
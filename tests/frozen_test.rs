@@ -0,0 +1,85 @@
+use trie_rcv::{
+    EliminationStrategies, GenericRankedChoiceVoteTrie, RankedChoiceVoteTrie, TrieTabulation
+};
+use trie_rcv::vote::{GenericRankedVote, RankedVote};
+
+const STRATEGIES: [EliminationStrategies; 4] = [
+    EliminationStrategies::EliminateAll,
+    EliminationStrategies::DowdallScoring,
+    EliminationStrategies::RankedPairs,
+    EliminationStrategies::CondorcetRankedPairs
+];
+
+fn build_votes() -> Vec<RankedVote> {
+    let vote_rankings = vec![
+        vec![1, 2, 3], vec![1, 3, 2], vec![2, 3, 1], vec![2, 1],
+        vec![3, 2, 1], vec![3, 1], vec![4, 3, 2, 1], vec![4, 2],
+        vec![2, 3], vec![1, -1], vec![3, 2, -2], vec![5, 4, 3]
+    ];
+    let mut votes = Vec::new();
+    for repeats in 1..=10 {
+        for (index, rankings) in vote_rankings.iter().enumerate() {
+            if (repeats + index) % 4 == 0 { continue }
+            votes.push(RankedVote::from_vector(rankings).unwrap());
+        }
+    }
+    votes
+}
+
+#[test]
+fn test_frozen_trie_matches_trie() {
+    let mut rcv = RankedChoiceVoteTrie::new();
+    rcv.insert_votes(build_votes());
+    let mut frozen_rcv = rcv.freeze();
+    assert_eq!(frozen_rcv.get_num_votes(), rcv.get_num_votes());
+    assert_eq!(frozen_rcv.candidates(), &[1, 2, 3, 4, 5]);
+
+    // the same snapshot can be tabulated under every strategy
+    for strategy in STRATEGIES {
+        rcv.set_elimination_strategy(strategy);
+        frozen_rcv.set_elimination_strategy(strategy);
+        let election_result = rcv.tabulate();
+        assert_eq!(frozen_rcv.tabulate(), election_result);
+        assert_eq!(frozen_rcv.determine_winner(), election_result.winner);
+        assert_eq!(frozen_rcv.find_spoilers(), rcv.find_spoilers());
+        assert_eq!(
+            frozen_rcv.replay_tabulation(&election_result),
+            rcv.replay_tabulation(&election_result)
+        );
+    }
+}
+
+#[test]
+fn test_frozen_trie_excluded_candidates() {
+    let mut rcv = RankedChoiceVoteTrie::new();
+    rcv.insert_votes(build_votes());
+    let mut frozen_rcv = rcv.freeze();
+    let election_result = rcv.tabulate();
+
+    rcv.exclude_candidates(&[2]);
+    frozen_rcv.exclude_candidates(&[2, 9]);
+    assert_eq!(frozen_rcv.tabulate(), rcv.tabulate());
+
+    frozen_rcv.clear_excluded_candidates();
+    assert_eq!(frozen_rcv.tabulate(), election_result);
+}
+
+#[test]
+fn test_frozen_trie_string_candidates() {
+    let votes = vec![
+        vec!["zoe", "bob"], vec!["bob", "zoe"],
+        vec!["carol", "bob"], vec!["bob"], vec!["zoe", "carol"]
+    ];
+    let mut rcv = GenericRankedChoiceVoteTrie::new();
+    for vote in votes {
+        let candidates: Vec<String> =
+            vote.into_iter().map(String::from).collect();
+        rcv.insert_vote(
+            GenericRankedVote::from_candidates(&candidates).unwrap()
+        );
+    }
+
+    let frozen_rcv = rcv.freeze();
+    assert_eq!(frozen_rcv.tabulate(), rcv.tabulate());
+    assert_eq!(frozen_rcv.determine_winner(), Some("bob".to_string()));
+}
@@ -1,6 +1,9 @@
-use trie_rcv;
-use trie_rcv::{EliminationStrategies, RankedChoiceVoteTrie};
-use trie_rcv::vote::{SpecialVotes, RankedVote};
+use trie_rcv::{
+    EliminationStrategies, GenericRankedChoiceVoteTrie, RankedChoiceVoteTrie,
+    SpoilerEffect, TrieTabulation
+};
+use trie_rcv::scoring::BigRational;
+use trie_rcv::vote::{GenericRankedVote, SpecialVotes, RankedVote};
 
 const WITHOLD_VOTE_VAL: i32 = SpecialVotes::WITHHOLD.to_int();
 const ABSTAIN_VOTE_VAL: i32 = SpecialVotes::ABSTAIN.to_int();
@@ -74,6 +77,25 @@ fn test_tie_scenario() {
     assert_eq!(winner, None, "There should be a tie");
 }
 
+#[test]
+fn test_eliminated_ballots_all_exhausted() {
+    let votes = RankedVote::from_vectors(&vec![
+        vec![1, 2],
+        vec![1],
+        vec![2],
+        vec![2],
+        vec![3]
+    ]).unwrap();
+
+    let rcv = RankedChoiceVoteTrie::new();
+    let winner = rcv.run_election(votes);
+    println!("WINNER = {:?}", winner);
+    assert_eq!(
+        winner, None,
+        "Candidate 3's vote has nowhere to go, election can't progress"
+    );
+}
+
 #[test]
 fn test_withold_vote_end() {
     let votes = RankedVote::from_vectors(&vec![
@@ -142,6 +164,54 @@ fn test_dowdall_elimination() {
     assert_eq!(winner, Some(6));
 }
 
+#[test]
+fn test_exact_dowdall_scores() {
+    // ten 10th place rankings add up to exactly one first place ranking
+    let mut raw_votes = vec![vec![2]];
+    for _ in 0..10 {
+        raw_votes.push(vec![3, 4, 5, 6, 7, 8, 9, 10, 11, 1]);
+    }
+
+    let mut rcv = RankedChoiceVoteTrie::new();
+    rcv.insert_votes(RankedVote::from_vectors(&raw_votes).unwrap());
+    let dowdall_scores = rcv.dowdall_scores();
+    let one = BigRational::from_integer(1.into());
+    assert_eq!(dowdall_scores[&1], one);
+    assert_eq!(dowdall_scores[&2], one);
+    assert_eq!(dowdall_scores[&3], BigRational::from_integer(10.into()));
+
+    // borda count with 11 candidates, first choices worth 10 points
+    let borda_scores = rcv.positional_scores(|ranking| {
+        BigRational::from_integer((10 - ranking as i64).into())
+    });
+    assert_eq!(borda_scores[&1], BigRational::from_integer(10.into()));
+    assert_eq!(borda_scores[&2], BigRational::from_integer(10.into()));
+    assert_eq!(borda_scores[&4], BigRational::from_integer(90.into()));
+}
+
+#[test]
+fn test_rank_histogram() {
+    let votes = RankedVote::from_vectors(&vec![
+        vec![1, 2, 3], vec![1, 3], vec![2, 1], vec![3], vec![-1], vec![2, -2]
+    ]).unwrap();
+    let mut rcv = RankedChoiceVoteTrie::new();
+    rcv.insert_votes(votes);
+
+    let histogram = rcv.rank_histogram();
+    assert_eq!(histogram.len(), 3);
+    assert_eq!(histogram[&1].ranked, vec![2, 1, 0]);
+    assert_eq!(histogram[&1].unranked, 3);
+    assert_eq!(histogram[&2].ranked, vec![2, 1, 0]);
+    assert_eq!(histogram[&3].ranked, vec![1, 1, 1]);
+    // every ballot either ranks a candidate or leaves them unranked
+    for rank_counts in histogram.values() {
+        assert_eq!(
+            rank_counts.num_ranked() + rank_counts.unranked,
+            rcv.get_num_votes()
+        );
+    }
+}
+
 #[test]
 fn test_all_elimination() {
     let votes = RankedVote::from_vectors(&vec![
@@ -170,6 +240,7 @@ fn test_spoiler_vote() {
     let rcv_vote_type3 = vec![vec![B, T, S]];
     let rcv_vote_type4 = vec![vec![T, B, S]];
 
+    #[allow(clippy::needless_return)]
     fn repeat(num_votes: u64, vote_type: Vec<Vec<i32>>) -> Vec<Vec<i32>> {
         return (0..num_votes)
         .flat_map(|_| vote_type.clone())
@@ -188,6 +259,17 @@ fn test_spoiler_vote() {
     let winner = rcv.run_election(votes);
     println!("WINNER = {:?}", winner);
     assert_eq!(winner, Some(T as u32));
+
+    // S spoils the election for B, who would otherwise win outright
+    rcv.insert_votes(RankedVote::from_vectors(&raw_votes).unwrap());
+    let spoilers = rcv.find_spoilers();
+    println!("SPOILERS = {:?}", spoilers);
+    assert_eq!(spoilers, vec![SpoilerEffect {
+        spoiler: S as u32,
+        original_winner: Some(T as u32),
+        winner_without_spoiler: Some(B as u32)
+    }]);
+    assert_eq!(rcv.determine_winner(), Some(T as u32));
 }
 
 #[test]
@@ -219,4 +301,230 @@ fn test_condorcet_vote() {
     let winner = rcv.run_election(votes);
     println!("WINNER = {:?}", winner);
     assert_eq!(winner, Some(B as u32));
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_string_candidates() {
+    let votes = vec![
+        GenericRankedVote::from_candidates(&["alice", "bob", "carol"]),
+        GenericRankedVote::from_candidates(&["alice", "bob"]),
+        GenericRankedVote::from_candidates(&["carol"]),
+        GenericRankedVote::from_candidates(&["carol", "bob"]),
+        GenericRankedVote::from_candidates(&["bob", "alice"])
+    ].into_iter().collect::<Result<Vec<_>, _>>().unwrap();
+
+    let rcv = GenericRankedChoiceVoteTrie::new();
+    let winner = rcv.run_election(votes);
+    println!("WINNER = {:?}", winner);
+    assert_eq!(
+        winner, Some("alice"),
+        "Vote bob > alice should go to alice, leading to alice winning"
+    );
+}
+
+#[test]
+fn test_declared_candidates() {
+    let votes = RankedVote::from_vectors(&vec![
+        vec![1, 2],
+        vec![1, 3],
+        vec![2, 3],
+        vec![3]
+    ]).unwrap();
+
+    let mut rcv = RankedChoiceVoteTrie::new();
+    rcv.set_elimination_strategy(EliminationStrategies::EliminateAll);
+    rcv.declare_candidates(&[4, 5]);
+    rcv.insert_votes(votes);
+    let result = rcv.tabulate();
+    println!("RESULT = {:?}", result);
+
+    let first_round = &result.rounds[0];
+    assert_eq!(first_round.tallies.get(&4), Some(&0));
+    assert_eq!(first_round.tallies.get(&5), Some(&0));
+    assert_eq!(
+        first_round.eliminated, vec![4, 5],
+        "Declared candidates without votes should be eliminated first"
+    );
+    assert_eq!(result.winner, rcv.determine_winner());
+}
+
+#[test]
+fn test_lower_ranked_only_candidate() {
+    let votes = RankedVote::from_vectors(&vec![
+        vec![1, 3],
+        vec![1, 3],
+        vec![2, 3],
+        vec![2, 1],
+        vec![4, 3]
+    ]).unwrap();
+
+    let mut rcv = RankedChoiceVoteTrie::new();
+    rcv.insert_votes(votes);
+    let result = rcv.tabulate();
+    println!("RESULT = {:?}", result);
+
+    // candidate 3 is never a first choice, but still counts as a candidate
+    assert_eq!(result.rounds[0].tallies.get(&3), Some(&0));
+    assert_eq!(result.rounds[0].eliminated, vec![3]);
+    // vote 4 > 3 is exhausted instead of reviving candidate 3
+    assert!(result.rounds.iter().skip(1).all(
+        |round| !round.tallies.contains_key(&3)
+    ));
+    assert_eq!(result.winner, Some(1));
+}
+
+#[test]
+fn test_excluded_candidates() {
+    let raw_votes = vec![
+        vec![1, 6, 15],
+        vec![1, 2, 6, 15, 5, 4, 7, 3, 11],
+        vec![6, 15, 1, 11, 10, 16, 17, 8, 2, 3, 5, 7],
+        vec![9, 8, 6, 11, 13, 3, 1],
+        vec![13, 14, 16, 6, 3, 4, 5, 2, 1, 8, 9],
+        vec![6, 1, WITHOLD_VOTE_VAL],
+        vec![6, ABSTAIN_VOTE_VAL]
+    ];
+    // same ballots with candidate 6 struck out of every ballot
+    let stripped_votes: Vec<Vec<i32>> = raw_votes.iter().map(
+        |raw_vote| raw_vote.iter().cloned().filter(|&x| x != 6).collect()
+    ).collect();
+
+    for strategy in [
+        EliminationStrategies::EliminateAll,
+        EliminationStrategies::DowdallScoring,
+        EliminationStrategies::RankedPairs,
+        EliminationStrategies::CondorcetRankedPairs
+    ] {
+        let mut rcv = RankedChoiceVoteTrie::new();
+        rcv.set_elimination_strategy(strategy);
+        rcv.insert_votes(RankedVote::from_vectors(&raw_votes).unwrap());
+        rcv.exclude_candidates(&[6]);
+        let result = rcv.tabulate();
+
+        let mut stripped_rcv = RankedChoiceVoteTrie::new();
+        stripped_rcv.set_elimination_strategy(strategy);
+        stripped_rcv.insert_votes(
+            RankedVote::from_vectors(&stripped_votes).unwrap()
+        );
+        let stripped_result = stripped_rcv.tabulate();
+        println!("RESULT = {:?}", result);
+
+        assert_eq!(result.excluded_candidates, vec![6]);
+        assert!(!result.rounds[0].tallies.contains_key(&6));
+        assert_eq!(result.rounds, stripped_result.rounds);
+        assert_eq!(result.winner, stripped_result.winner);
+        assert_eq!(
+            rcv.run_election(RankedVote::from_vectors(&raw_votes).unwrap()),
+            stripped_result.winner
+        );
+    }
+}
+
+#[test]
+fn test_binary_round_trip() {
+    let raw_votes = vec![
+        vec![1, 6, 15],
+        vec![1, 2, 6, 15, 5, 4, 7, 3, 11],
+        vec![6, 15, 1, 11, 10, 16, 17, 8, 2, 3, 5, 7],
+        vec![9, 8, 6, 11, 13, 3, 1],
+        vec![13, 14, 16, 6, 3, 4, 5, 2, 1, 8, 9],
+        vec![6, 1, WITHOLD_VOTE_VAL],
+        vec![ABSTAIN_VOTE_VAL]
+    ];
+
+    let mut rcv = RankedChoiceVoteTrie::new();
+    rcv.declare_candidates(&[20]);
+    rcv.insert_votes(RankedVote::from_vectors(&raw_votes).unwrap());
+    let mut bytes: Vec<u8> = Vec::new();
+    rcv.write_to(&mut bytes).unwrap();
+    let mut loaded_rcv =
+        RankedChoiceVoteTrie::read_from(&mut bytes.as_slice()).unwrap();
+    assert_eq!(loaded_rcv.get_num_votes(), rcv.get_num_votes());
+
+    // serializing the loaded trie again should give identical bytes
+    let mut reserialized_bytes: Vec<u8> = Vec::new();
+    loaded_rcv.write_to(&mut reserialized_bytes).unwrap();
+    assert_eq!(bytes, reserialized_bytes);
+
+    for strategy in [
+        EliminationStrategies::EliminateAll,
+        EliminationStrategies::DowdallScoring,
+        EliminationStrategies::RankedPairs,
+        EliminationStrategies::CondorcetRankedPairs
+    ] {
+        rcv.set_elimination_strategy(strategy);
+        loaded_rcv.set_elimination_strategy(strategy);
+        assert_eq!(rcv.tabulate(), loaded_rcv.tabulate());
+    }
+}
+
+#[test]
+fn test_binary_round_trip_string_candidates() {
+    let mut rcv = GenericRankedChoiceVoteTrie::new();
+    rcv.insert_votes(vec![
+        GenericRankedVote::from_candidates(&["alice".to_string()]).unwrap(),
+        GenericRankedVote::new(
+            vec!["bob".to_string()], Some(SpecialVotes::WITHHOLD)
+        ).unwrap()
+    ]);
+
+    let mut bytes: Vec<u8> = Vec::new();
+    rcv.write_to(&mut bytes).unwrap();
+    let loaded_rcv =
+        GenericRankedChoiceVoteTrie::<String>::read_from(&mut bytes.as_slice())
+        .unwrap();
+    assert_eq!(loaded_rcv.tabulate(), rcv.tabulate());
+    assert!(
+        GenericRankedChoiceVoteTrie::<String>::read_from(&mut &bytes[..8])
+        .is_err()
+    );
+}
+
+#[test]
+fn test_merge_precinct_tries() {
+    let raw_votes = vec![
+        vec![1, 6, 15],
+        vec![1, 2, 6, 15, 5, 4, 7, 3, 11],
+        vec![6, 15, 1, 11, 10, 16, 17, 8, 2, 3, 5, 7],
+        vec![9, 8, 6, 11, 13, 3, 1],
+        vec![13, 14, 16, 6, 3, 4, 5, 2, 1, 8, 9],
+        vec![6, 1, WITHOLD_VOTE_VAL],
+        vec![1, 6, 15],
+        vec![ABSTAIN_VOTE_VAL]
+    ];
+
+    let mut single_rcv = RankedChoiceVoteTrie::new();
+    single_rcv.insert_votes(RankedVote::from_vectors(&raw_votes).unwrap());
+
+    // count ballots in three separate precincts then combine them
+    let precinct_tries: Vec<RankedChoiceVoteTrie> = raw_votes.chunks(3).map(
+        |precinct_votes| {
+            let mut rcv = RankedChoiceVoteTrie::new();
+            rcv.insert_votes(
+                RankedVote::from_vectors(&precinct_votes.to_vec()).unwrap()
+            );
+            rcv
+        }
+    ).collect();
+
+    let mut merged_rcv: RankedChoiceVoteTrie = precinct_tries.into_iter().sum();
+    assert_eq!(merged_rcv.get_num_votes(), single_rcv.get_num_votes());
+
+    for strategy in [
+        EliminationStrategies::EliminateAll,
+        EliminationStrategies::DowdallScoring,
+        EliminationStrategies::RankedPairs,
+        EliminationStrategies::CondorcetRankedPairs
+    ] {
+        single_rcv.set_elimination_strategy(strategy);
+        merged_rcv.set_elimination_strategy(strategy);
+        assert_eq!(merged_rcv.tabulate(), single_rcv.tabulate());
+    }
+
+    let mut extended_rcv = RankedChoiceVoteTrie::new();
+    extended_rcv.set_elimination_strategy(
+        EliminationStrategies::CondorcetRankedPairs
+    );
+    extended_rcv.extend(vec![merged_rcv]);
+    assert_eq!(extended_rcv.tabulate(), single_rcv.tabulate());
+}
@@ -0,0 +1,107 @@
+use trie_rcv::{EliminationStrategies, RankedChoiceVoteTrie, TrieTabulation};
+use trie_rcv::binary::BinaryFormatErrors;
+use trie_rcv::mapped::MappedTrie;
+use trie_rcv::vote::RankedVote;
+
+const STRATEGIES: [EliminationStrategies; 4] = [
+    EliminationStrategies::EliminateAll,
+    EliminationStrategies::DowdallScoring,
+    EliminationStrategies::RankedPairs,
+    EliminationStrategies::CondorcetRankedPairs
+];
+
+fn build_trie() -> RankedChoiceVoteTrie {
+    let vote_rankings = vec![
+        vec![1, 2, 3], vec![1, 3, 2], vec![2, 3, 1], vec![2, 1],
+        vec![3, 2, 1], vec![3, 1], vec![4, 3, 2, 1], vec![4, 2],
+        vec![2, 3], vec![1, -1], vec![3, 2, -2], vec![5, 4, 3]
+    ];
+    let mut rcv = RankedChoiceVoteTrie::new();
+    for repeats in 1..=10 {
+        for (index, rankings) in vote_rankings.iter().enumerate() {
+            if (repeats + index) % 4 == 0 { continue }
+            rcv.insert_vote(RankedVote::from_vector(rankings).unwrap());
+        }
+    }
+    rcv
+}
+
+#[test]
+fn test_mapped_trie_matches_trie() {
+    let mut rcv = build_trie();
+    let mut bytes = Vec::new();
+    rcv.write_mapped(&mut bytes).unwrap();
+
+    let mut mapped_rcv = MappedTrie::from_bytes(bytes.as_slice()).unwrap();
+    assert_eq!(mapped_rcv.get_num_votes(), rcv.get_num_votes());
+    assert_eq!(mapped_rcv.get_num_nodes(), rcv.freeze().get_num_nodes());
+
+    for strategy in STRATEGIES {
+        rcv.set_elimination_strategy(strategy);
+        mapped_rcv.set_elimination_strategy(strategy);
+        let election_result = rcv.tabulate();
+        assert_eq!(mapped_rcv.tabulate(), election_result);
+        assert_eq!(mapped_rcv.find_spoilers(), rcv.find_spoilers());
+        assert_eq!(
+            mapped_rcv.replay_tabulation(&election_result),
+            rcv.replay_tabulation(&election_result)
+        );
+    }
+
+    rcv.exclude_candidates(&[3]);
+    mapped_rcv.exclude_candidates(&[3]);
+    assert_eq!(mapped_rcv.tabulate(), rcv.tabulate());
+}
+
+#[test]
+fn test_mapped_trie_rejects_corrupt_bytes() {
+    let mut bytes = Vec::new();
+    build_trie().write_mapped(&mut bytes).unwrap();
+
+    let truncated_bytes = &bytes[..bytes.len() - 1];
+    assert!(matches!(
+        MappedTrie::from_bytes(truncated_bytes),
+        Err(BinaryFormatErrors::InvalidNodeTable)
+    ));
+
+    // give the last node a subtree extending past the end of the table
+    let mut corrupt_bytes = bytes.clone();
+    let last_record = corrupt_bytes.len() - 16;
+    corrupt_bytes[last_record + 4..last_record + 8]
+        .copy_from_slice(&2u32.to_le_bytes());
+    assert!(matches!(
+        MappedTrie::from_bytes(corrupt_bytes.as_slice()),
+        Err(BinaryFormatErrors::InvalidNodeTable)
+    ));
+
+    // give the last node no votes, which tabulation can't handle
+    let mut corrupt_bytes = bytes.clone();
+    corrupt_bytes[last_record + 8..last_record + 16]
+        .copy_from_slice(&0u64.to_le_bytes());
+    assert!(matches!(
+        MappedTrie::from_bytes(corrupt_bytes.as_slice()),
+        Err(BinaryFormatErrors::InconsistentVoteCounts)
+    ));
+
+    assert!(matches!(
+        MappedTrie::from_bytes(&b"TRCV\x01"[..]),
+        Err(BinaryFormatErrors::InvalidMagicBytes)
+    ));
+}
+
+#[cfg(feature = "mmap")]
+#[test]
+fn test_memory_mapped_file() {
+    let rcv = build_trie();
+    let path = std::env::temp_dir().join(format!(
+        "trie_rcv_mapped_test_{}.trcm", std::process::id()
+    ));
+    let mut file = std::fs::File::create(&path).unwrap();
+    rcv.write_mapped(&mut file).unwrap();
+    drop(file);
+
+    let mapped_rcv = MappedTrie::open(&path).unwrap();
+    assert_eq!(mapped_rcv.tabulate(), rcv.tabulate());
+    drop(mapped_rcv);
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,50 @@
+#![cfg(feature = "rayon")]
+use trie_rcv::{EliminationStrategies, RankedChoiceVoteTrie, TrieTabulation};
+use trie_rcv::vote::RankedVote;
+
+fn build_votes() -> Vec<RankedVote> {
+    let vote_rankings = vec![
+        vec![1, 2, 3], vec![1, 3, 2], vec![2, 3, 1], vec![2, 1],
+        vec![3, 2, 1], vec![3, 1], vec![4, 3, 2, 1], vec![4, 2],
+        vec![2, 3], vec![1, -1], vec![3, 2, -2], vec![2]
+    ];
+    let mut votes = Vec::new();
+    for repeats in 1..=40 {
+        for (index, rankings) in vote_rankings.iter().enumerate() {
+            // vary the vote counts so the strategies don't all tie
+            if (repeats + index) % 3 == 0 { continue }
+            votes.push(RankedVote::from_vector(rankings).unwrap());
+        }
+    }
+    votes
+}
+
+#[test]
+fn test_par_insert_votes_matches_insert_votes() {
+    let strategies = [
+        EliminationStrategies::EliminateAll,
+        EliminationStrategies::DowdallScoring,
+        EliminationStrategies::RankedPairs,
+        EliminationStrategies::CondorcetRankedPairs
+    ];
+
+    for strategy in strategies {
+        let mut sequential_rcv = RankedChoiceVoteTrie::new();
+        sequential_rcv.set_elimination_strategy(strategy);
+        sequential_rcv.insert_votes(build_votes());
+
+        let mut parallel_rcv = RankedChoiceVoteTrie::new();
+        parallel_rcv.set_elimination_strategy(strategy);
+        parallel_rcv.par_insert_votes(build_votes());
+
+        assert_eq!(
+            parallel_rcv.get_num_votes(), sequential_rcv.get_num_votes()
+        );
+        let sequential_result = sequential_rcv.tabulate();
+        assert_eq!(parallel_rcv.tabulate(), sequential_result);
+        assert_eq!(sequential_rcv.par_tabulate(), sequential_result);
+        assert_eq!(
+            parallel_rcv.par_determine_winner(), sequential_result.winner
+        );
+    }
+}
@@ -0,0 +1,79 @@
+use trie_rcv::precincts::PrecinctTree;
+use trie_rcv::vote::RankedVote;
+use trie_rcv::EliminationStrategies;
+
+#[test]
+fn test_precinct_reports_follow_global_rounds() {
+    let mut precinct_tree = PrecinctTree::new("District 1");
+    let district = precinct_tree.root();
+    let ward_a = precinct_tree.add_jurisdiction(district, "Ward A");
+    let ward_b = precinct_tree.add_jurisdiction(district, "Ward B");
+    let precinct_a1 = precinct_tree.add_jurisdiction(ward_a, "Precinct A1");
+    let precinct_a2 = precinct_tree.add_jurisdiction(ward_a, "Precinct A2");
+    let precinct_b1 = precinct_tree.add_jurisdiction(ward_b, "Precinct B1");
+    precinct_tree.set_elimination_strategy(EliminationStrategies::EliminateAll);
+
+    precinct_tree.insert_votes(precinct_a1, RankedVote::from_vectors(&vec![
+        vec![1, 2], vec![1, 2], vec![3, 2]
+    ]).unwrap());
+    precinct_tree.insert_votes(precinct_a2, RankedVote::from_vectors(&vec![
+        vec![2, 1], vec![3, 1]
+    ]).unwrap());
+    precinct_tree.insert_votes(precinct_b1, RankedVote::from_vectors(&vec![
+        vec![2, 3], vec![2], vec![3, 2], vec![3, 1]
+    ]).unwrap());
+
+    let result = precinct_tree.tabulate();
+    println!("RESULT = {:?}", result);
+    // 1: 2, 2: 3, 3: 4 -> 1 is eliminated, then 2 wins 5 to 4
+    assert_eq!(result.rounds.len(), 2);
+    assert_eq!(result.rounds[0].eliminated, vec![1]);
+    assert_eq!(result.winner, Some(2));
+
+    let district_report = precinct_tree.report(district);
+    assert_eq!(district_report.result, result);
+    assert_eq!(district_report.num_votes, 9);
+
+    // precinct A1's ballots for 1 move on to 2 in the global final round
+    let precinct_report = precinct_tree.report(precinct_a1);
+    println!("PRECINCT_REPORT = {:?}", precinct_report);
+    assert_eq!(precinct_report.name, "Precinct A1");
+    assert_eq!(precinct_report.first_round().unwrap().tallies.get(&1), Some(&2));
+    let final_round = precinct_report.final_round().unwrap();
+    assert_eq!(final_round.tallies.get(&2), Some(&2));
+    assert_eq!(final_round.tallies.get(&3), Some(&1));
+    assert!(!final_round.tallies.contains_key(&1));
+
+    // ward tallies add up to the district tallies in every round
+    let ward_a_report = precinct_tree.report(ward_a);
+    let ward_b_report = precinct_tree.report(ward_b);
+    for (round_index, round) in result.rounds.iter().enumerate() {
+        for (candidate, num_votes) in &round.tallies {
+            let ward_a_votes =
+                ward_a_report.result.rounds[round_index].tallies[candidate];
+            let ward_b_votes =
+                ward_b_report.result.rounds[round_index].tallies[candidate];
+            assert_eq!(ward_a_votes + ward_b_votes, *num_votes);
+        }
+    }
+    assert_eq!(precinct_tree.reports().len(), 6);
+}
+
+#[test]
+fn test_precinct_aggregates_update_on_insert() {
+    let mut precinct_tree = PrecinctTree::new("District 1");
+    let precinct = precinct_tree.add_jurisdiction(
+        precinct_tree.root(), "Precinct 1"
+    );
+    precinct_tree.insert_vote(
+        precinct, RankedVote::from_vector(&vec![1, 2]).unwrap()
+    );
+    assert_eq!(precinct_tree.aggregate(precinct_tree.root()).get_num_votes(), 1);
+    assert_eq!(precinct_tree.tabulate().winner, Some(1));
+
+    precinct_tree.insert_votes(precinct, RankedVote::from_vectors(&vec![
+        vec![2, 1], vec![2]
+    ]).unwrap());
+    assert_eq!(precinct_tree.aggregate(precinct_tree.root()).get_num_votes(), 3);
+    assert_eq!(precinct_tree.tabulate().winner, Some(2));
+}
@@ -0,0 +1,52 @@
+use std::collections::BTreeMap;
+use trie_rcv::RankedChoiceVoteTrie;
+use trie_rcv::prefix::PrefixPattern;
+use trie_rcv::vote::{RankedVote, SpecialVotes, VoteValues};
+
+const WITHOLD_VOTE_VAL: i32 = SpecialVotes::WITHHOLD.to_int();
+
+fn build_trie() -> RankedChoiceVoteTrie {
+    let raw_votes = vec![
+        vec![3, 7, 1], vec![3, 7], vec![3, 1, 7], vec![5, 3], vec![5, 7],
+        vec![5], vec![5, WITHOLD_VOTE_VAL], vec![1, 7, 3], vec![5, 3, 1]
+    ];
+    let mut rcv = RankedChoiceVoteTrie::new();
+    rcv.insert_votes(RankedVote::from_vectors(&raw_votes).unwrap());
+    rcv
+}
+
+#[test]
+fn test_prefix_queries() {
+    let rcv = build_trie();
+    assert_eq!(rcv.count_prefix(&[3, 7]), 2);
+    assert_eq!(rcv.count_prefix(&[5]), 5);
+    assert_eq!(rcv.count_prefix(&[7]), 0);
+    assert_eq!(rcv.count_prefix(&[]), rcv.get_num_votes());
+    assert_eq!(rcv.count_prefix(&[3, 3]), 0);
+
+    // one ballot ranking only 5 isn't listed among the next choices
+    assert_eq!(rcv.next_choices(&[5]), BTreeMap::from([
+        (VoteValues::Candidate(3), 2),
+        (VoteValues::Candidate(7), 1),
+        (VoteValues::SpecialVote(SpecialVotes::WITHHOLD), 1)
+    ]));
+    assert!(rcv.next_choices(&[2]).is_empty());
+    assert_eq!(rcv.next_choices(&[]).values().sum::<u64>(), rcv.get_num_votes());
+}
+
+#[test]
+fn test_wildcard_queries() {
+    let rcv = build_trie();
+    let ranks_7_second = [
+        PrefixPattern::AnyCandidate, PrefixPattern::Candidate(7)
+    ];
+    assert_eq!(rcv.count_matching(&ranks_7_second), 4);
+    assert_eq!(rcv.next_choices_matching(&ranks_7_second), BTreeMap::from([
+        (VoteValues::Candidate(1), 1),
+        (VoteValues::Candidate(3), 1)
+    ]));
+
+    // withheld votes don't count as a ranked candidate
+    let ranks_two_candidates = [PrefixPattern::AnyCandidate; 2];
+    assert_eq!(rcv.count_matching(&ranks_two_candidates), 7);
+}
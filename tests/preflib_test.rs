@@ -0,0 +1,83 @@
+use trie_rcv::{RankedChoiceVoteTrie, TrieTabulation};
+use trie_rcv::formats::preflib::{read_preflib, PreflibDataType, PreflibErrors};
+use trie_rcv::vote::{RankedVote, SpecialVotes};
+
+const TOI_FILE: &str = "# FILE NAME: 00000-00000001.toi
+# TITLE: Example Election
+# DATA TYPE: toi
+# NUMBER ALTERNATIVES: 4
+# ALTERNATIVE NAME 1: Alice
+# ALTERNATIVE NAME 2: Bob
+# ALTERNATIVE NAME 3: Carol
+# ALTERNATIVE NAME 4: Dave
+# NUMBER VOTERS: 16
+# NUMBER UNIQUE ORDERS: 4
+6: 1,2,3
+5: 2, {1,3}, 4
+3: 3,1
+2: {1,2,3,4}
+";
+
+#[test]
+fn test_read_preflib_toi() {
+    let election = read_preflib(TOI_FILE.as_bytes()).unwrap();
+    let metadata = &election.metadata;
+    assert_eq!(metadata.data_type, PreflibDataType::Toi);
+    assert_eq!(metadata.num_alternatives, 4);
+    assert_eq!(metadata.title.as_deref(), Some("Example Election"));
+    assert_eq!(metadata.alternative_names[&3], "Carol");
+    assert_eq!(metadata.headers.len(), 10);
+    assert_eq!(
+        metadata.headers[8],
+        ("NUMBER VOTERS".to_string(), "16".to_string())
+    );
+
+    // orders are cut short at their first tie
+    assert_eq!(election.num_truncated_votes, 7);
+    let mut expected_rcv = RankedChoiceVoteTrie::new();
+    expected_rcv.declare_candidates(&[1, 2, 3, 4]);
+    let raw_votes = vec![(vec![1, 2, 3], 6), (vec![2], 5), (vec![3, 1], 3)];
+    for (rankings, count) in raw_votes {
+        expected_rcv.insert_weighted_vote(
+            RankedVote::from_vector(&rankings).unwrap(), count
+        );
+    }
+    expected_rcv.insert_weighted_vote(
+        RankedVote::new(vec![], Some(SpecialVotes::ABSTAIN)).unwrap(), 2
+    );
+
+    assert_eq!(election.trie.get_num_votes(), 16);
+    assert_eq!(election.trie.rank_histogram(), expected_rcv.rank_histogram());
+    assert_eq!(election.trie.tabulate(), expected_rcv.tabulate());
+}
+
+#[test]
+fn test_preflib_strict_orders() {
+    let soc_file = "# DATA TYPE: soc\n# NUMBER ALTERNATIVES: 3\n\
+        2: 1,2,3\n1: 3,2,1\n";
+    let election = read_preflib(soc_file.as_bytes()).unwrap();
+    assert_eq!(election.trie.determine_winner(), Some(1));
+    assert_eq!(election.num_truncated_votes, 0);
+
+    let incomplete = "# DATA TYPE: soc\n# NUMBER ALTERNATIVES: 3\n\
+        2: 1,2,3\n1: 3,2\n";
+    let error = read_preflib(incomplete.as_bytes()).unwrap_err();
+    assert!(matches!(error, PreflibErrors::IncompleteOrder { line: 4 }));
+
+    let tied = "# DATA TYPE: soi\n# NUMBER ALTERNATIVES: 3\n1: {1,2}\n";
+    let error = read_preflib(tied.as_bytes()).unwrap_err();
+    assert!(matches!(error, PreflibErrors::TiedRankings { line: 3 }));
+
+    let unknown = "# DATA TYPE: soi\n# NUMBER ALTERNATIVES: 3\n1: 1,4\n";
+    let error = read_preflib(unknown.as_bytes()).unwrap_err();
+    assert_eq!(error.to_string(), "line 3: invalid alternative 4");
+
+    let zero_count = "# DATA TYPE: soi\n# NUMBER ALTERNATIVES: 3\n0: 1,2\n2: 2\n";
+    let error = read_preflib(zero_count.as_bytes()).unwrap_err();
+    assert!(matches!(error, PreflibErrors::ZeroCount { line: 3 }));
+    assert_eq!(error.line(), Some(3));
+
+    let missing_type = "# NUMBER ALTERNATIVES: 3\n1: 1,2\n";
+    let error = read_preflib(missing_type.as_bytes()).unwrap_err();
+    assert!(matches!(error, PreflibErrors::MissingHeader("DATA TYPE")));
+}
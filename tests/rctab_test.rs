@@ -0,0 +1,227 @@
+use std::collections::BTreeMap;
+use trie_rcv::{RankedChoiceVoteTrie, VoteFlows};
+use trie_rcv::rctab::{
+    summary_csv, summary_json, BallotRules, ExhaustionCounts, ExhaustionReasons,
+    OvervoteRule, RankMark, RctabRules, RctabSummaryConfig, TiebreakMode
+};
+use trie_rcv::vote::{RankedVote, SpecialVotes};
+
+const ABSTAIN_VOTE_VAL: i32 = SpecialVotes::ABSTAIN.to_int();
+
+fn build_trie() -> RankedChoiceVoteTrie {
+    let mut raw_votes = vec![vec![1]; 5];
+    raw_votes.extend(vec![vec![2, 1]; 3]);
+    raw_votes.extend(vec![vec![3, 2]; 2]);
+    raw_votes.extend([vec![4, 3], vec![ABSTAIN_VOTE_VAL]]);
+    let mut rcv = RankedChoiceVoteTrie::new();
+    rcv.insert_votes(RankedVote::from_vectors(&raw_votes).unwrap());
+    rcv
+}
+
+#[test]
+fn test_ballot_rules() {
+    let marks = [
+        RankMark::Skipped, RankMark::Candidate(2), RankMark::Candidate(2),
+        RankMark::Overvote, RankMark::Skipped, RankMark::Skipped,
+        RankMark::Candidate(1)
+    ];
+    let (vote, reason) = BallotRules::default().interpret(&marks);
+    assert_eq!(vote.to_vector(), vec![2]);
+    assert_eq!(reason, Some(ExhaustionReasons::Overvote));
+
+    let rules = BallotRules {
+        overvote_rule: OvervoteRule::AlwaysSkipToNextRank,
+        ..BallotRules::default()
+    };
+    let (vote, reason) = rules.interpret(&marks);
+    assert_eq!(vote.to_vector(), vec![2, 1]);
+    assert_eq!(reason, None);
+
+    let rules = BallotRules {
+        overvote_rule: OvervoteRule::AlwaysSkipToNextRank,
+        max_skipped_ranks: Some(1),
+        exhaust_on_duplicate_candidate: false
+    };
+    assert_eq!(
+        rules.interpret(&marks).1, Some(ExhaustionReasons::SkippedRanks)
+    );
+    let rules = BallotRules {
+        exhaust_on_duplicate_candidate: true, ..BallotRules::default()
+    };
+    assert_eq!(
+        rules.interpret(&marks).1, Some(ExhaustionReasons::DuplicateCandidate)
+    );
+
+    // ballots that rank nobody abstain
+    let (vote, reason) = BallotRules::default().interpret(&[RankMark::Overvote]);
+    assert_eq!(vote.to_vector(), vec![ABSTAIN_VOTE_VAL as i64]);
+    assert_eq!(reason, Some(ExhaustionReasons::Overvote));
+}
+
+#[test]
+fn test_rctab_tiebreaks() {
+    let rcv = build_trie();
+    let rules = RctabRules {
+        tiebreak_mode: TiebreakMode::PreviousRoundCountsThenRandom,
+        ..RctabRules::default()
+    };
+    let result = rcv.tabulate_rctab(&rules);
+    assert_eq!(result.winner, Some(1));
+    assert_eq!(result.total_ballots, 12);
+    assert_eq!(result.undervotes, 1);
+
+    let rounds = &result.rounds;
+    assert_eq!(rounds.len(), 4);
+    assert_eq!(rounds[0].tallies, BTreeMap::from([(1, 5), (2, 3), (3, 2), (4, 1)]));
+    assert_eq!(rounds[0].threshold, 6);
+    // 3 and 2 tie in round 2, but 3 had fewer votes in round 1
+    assert_eq!(rounds[1].eliminated.keys().collect::<Vec<_>>(), vec![&3]);
+    assert_eq!(rounds[1].eliminated[&3], VoteFlows {
+        transfers: BTreeMap::from([(2, 2)]),
+        exhausted_votes: 1,
+        ..VoteFlows::default()
+    });
+    assert_eq!(rounds[2].continuing_ballots, 10);
+    assert_eq!(rounds[2].inactive_ballots.exhausted_votes, 1);
+    assert_eq!(rounds[3].elected, Some(1));
+    assert_eq!(rounds[3].tallies, BTreeMap::from([(1, 8)]));
+
+    // the candidate listed last in the permutation loses ties
+    let rules = RctabRules {
+        tiebreak_mode: TiebreakMode::UsePermutation(vec![2, 1, 3, 4]),
+        ..RctabRules::default()
+    };
+    let result = rcv.tabulate_rctab(&rules);
+    assert_eq!(result.winner, Some(2));
+    assert_eq!(result.to_election_result().rounds[2].eliminated, vec![1]);
+
+    // random tie breaks are reproducible from the seed
+    let rules = RctabRules { random_seed: 42, ..RctabRules::default() };
+    assert_eq!(rcv.tabulate_rctab(&rules), rcv.tabulate_rctab(&rules));
+}
+
+#[test]
+fn test_rctab_batch_elimination() {
+    let mut raw_votes = vec![vec![1]; 9];
+    raw_votes.extend(vec![vec![2]; 6]);
+    raw_votes.extend(vec![vec![3, 2]; 2]);
+    raw_votes.push(vec![4, 3]);
+    let mut rcv = RankedChoiceVoteTrie::new();
+    rcv.insert_votes(RankedVote::from_vectors(&raw_votes).unwrap());
+
+    let rules = RctabRules { batch_elimination: true, ..RctabRules::default() };
+    let result = rcv.tabulate_rctab(&rules);
+    assert_eq!(result.winner, Some(1));
+    assert_eq!(result.rounds.len(), 2);
+    assert_eq!(
+        result.rounds[0].eliminated.keys().collect::<Vec<_>>(), vec![&3, &4]
+    );
+    assert_eq!(result.rounds[1].tallies, BTreeMap::from([(1, 9), (2, 8)]));
+    assert_eq!(result.rounds[1].threshold, 9);
+
+    // without batch elimination 4 goes first, then 3
+    let result = rcv.tabulate_rctab(&RctabRules::default());
+    assert_eq!(result.winner, Some(1));
+    assert_eq!(result.rounds.len(), 3);
+
+    let rules = RctabRules { minimum_vote_threshold: 3, ..RctabRules::default() };
+    let result = rcv.tabulate_rctab(&rules);
+    assert_eq!(result.rounds.len(), 2);
+}
+
+#[test]
+fn test_rctab_summaries() {
+    let rcv = build_trie();
+    let rules = RctabRules {
+        tiebreak_mode: TiebreakMode::PreviousRoundCountsThenRandom,
+        ..RctabRules::default()
+    };
+    let result = rcv.tabulate_rctab(&rules);
+    let config = RctabSummaryConfig {
+        contest: "Mayor".to_string(), ..RctabSummaryConfig::default()
+    };
+    let names = ["", "Alice", "Bob", "Carol", "Dave"];
+    let name = |candidate: &u32| names[*candidate as usize].to_string();
+
+    let json: serde_json::Value =
+        serde_json::from_str(&summary_json(&result, &config, name)).unwrap();
+    assert_eq!(json["config"]["contest"], "Mayor");
+    assert_eq!(json["summary"]["totalNumBallots"], "12");
+    assert_eq!(json["summary"]["undervotes"], 1);
+    assert_eq!(json["results"][0]["tally"]["Alice"], "5");
+    assert_eq!(json["results"][1]["tallyResults"][0]["eliminated"], "Carol");
+    assert_eq!(
+        json["results"][1]["tallyResults"][0]["transfers"],
+        serde_json::json!({"Bob": "2", "exhausted": "1"})
+    );
+    assert_eq!(json["results"][3]["tallyResults"][0]["elected"], "Alice");
+
+    let csv = summary_csv(&result, &config, name);
+    assert!(csv.starts_with("Contest Information\n"));
+    assert!(csv.contains("Winner(s),Alice\n"));
+    assert!(csv.contains("Alice,5,45.45%,0,5,45.45%,0,5,50.00%,3,8,100.00%,\n"));
+    assert!(csv.contains("Eliminated,Dave,,,Carol,,,Bob,,,,,\n"));
+    assert!(csv.contains("Inactive Ballots Total,0,,,0,,,1,,,3,,\n"));
+}
+
+#[test]
+fn test_rctab_inactive_ballots_by_reason() {
+    let mut ballots = vec![vec![RankMark::Candidate(1)]; 5];
+    ballots.extend(vec![vec![RankMark::Candidate(2), RankMark::Candidate(1)]; 4]);
+    ballots.extend([
+        vec![RankMark::Candidate(3), RankMark::Overvote, RankMark::Candidate(1)],
+        vec![
+            RankMark::Candidate(3), RankMark::Skipped, RankMark::Skipped,
+            RankMark::Candidate(2)
+        ],
+        vec![RankMark::Candidate(3), RankMark::Candidate(3)],
+        // cut short before ranking anyone, so not an undervote
+        vec![RankMark::Overvote, RankMark::Candidate(2)],
+        vec![RankMark::Skipped]
+    ]);
+    let ballot_rules = BallotRules {
+        max_skipped_ranks: Some(1), exhaust_on_duplicate_candidate: true,
+        ..BallotRules::default()
+    };
+    let mut rcv = RankedChoiceVoteTrie::new();
+    let mut exhausted_ballots = Vec::new();
+    for marks in &ballots {
+        let (vote, exhaustion_reason) = ballot_rules.interpret(marks);
+        if let Some(exhaustion_reason) = exhaustion_reason {
+            exhausted_ballots.push((vote.clone(), exhaustion_reason));
+        }
+        rcv.insert_vote(vote);
+    }
+
+    let rules = RctabRules::default();
+    let result = rcv.tabulate_rctab_with_exhausted_ballots(
+        &rules, &exhausted_ballots
+    );
+    assert_eq!(result.winner, Some(1));
+    assert_eq!(result.undervotes, 1);
+    assert_eq!(result.rounds[0].inactive_by_reason, ExhaustionCounts {
+        overvotes: 1, ..ExhaustionCounts::default()
+    });
+    assert_eq!(result.rounds[1].inactive_by_reason, ExhaustionCounts {
+        overvotes: 2, skipped_rankings: 1, repeated_rankings: 1
+    });
+    // the same tallies as without exhausted ballots
+    let plain_result = rcv.tabulate_rctab(&rules);
+    assert_eq!(plain_result.undervotes, 2);
+    assert_eq!(plain_result.rounds[1].tallies, result.rounds[1].tallies);
+
+    let config = RctabSummaryConfig::default();
+    let name = |candidate: &u32| candidate.to_string();
+    let json: serde_json::Value =
+        serde_json::from_str(&summary_json(&result, &config, name)).unwrap();
+    assert_eq!(json["results"][1]["inactiveBallots"], serde_json::json!({
+        "exhaustedChoices": "0", "overvotes": "2", "repeatedRankings": "1",
+        "skippedRankings": "1"
+    }));
+
+    let csv = summary_csv(&result, &config, name);
+    assert!(csv.contains("Inactive Ballots by Overvotes,1,,,2,,\n"));
+    assert!(csv.contains("Inactive Ballots by Skipped Rankings,0,,,1,,\n"));
+    assert!(csv.contains("Inactive Ballots by Repeated Rankings,0,,,1,,\n"));
+    assert!(csv.contains("Inactive Ballots Total,1,,,4,,\n"));
+}
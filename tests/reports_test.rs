@@ -0,0 +1,119 @@
+use std::collections::BTreeMap;
+use trie_rcv::{
+    EliminationReasons, InactiveBallots, RankedChoiceVoteTrie, VoteFlows, TrieTabulation
+};
+use trie_rcv::reports::{report_html, report_json, report_markdown};
+use trie_rcv::vote::{RankedVote, SpecialVotes};
+
+const WITHOLD_VOTE_VAL: i32 = SpecialVotes::WITHHOLD.to_int();
+const ABSTAIN_VOTE_VAL: i32 = SpecialVotes::ABSTAIN.to_int();
+
+fn build_trie() -> RankedChoiceVoteTrie {
+    let mut raw_votes = vec![vec![1]; 4];
+    raw_votes.extend(vec![vec![2, 1]; 2]);
+    raw_votes.extend(vec![vec![2]; 3]);
+    raw_votes.extend([
+        vec![3, 2], vec![3, WITHOLD_VOTE_VAL], vec![3, ABSTAIN_VOTE_VAL],
+        vec![ABSTAIN_VOTE_VAL]
+    ]);
+    let mut rcv = RankedChoiceVoteTrie::new();
+    rcv.insert_votes(RankedVote::from_vectors(&raw_votes).unwrap());
+    rcv
+}
+
+fn candidate_name(candidate: &u32) -> String {
+    ["", "Alice", "Bob", "Carol <C|D>"][*candidate as usize].to_string()
+}
+
+#[test]
+fn test_rounds_record_vote_flows() {
+    let result = build_trie().tabulate();
+    assert_eq!(result.winner, Some(2));
+    let first_round = &result.rounds[0];
+    assert_eq!(first_round.eliminated, vec![3]);
+    assert_eq!(first_round.elimination_reason, Some(EliminationReasons::FewestVotes));
+    assert_eq!(first_round.transfers, BTreeMap::from([(3, VoteFlows {
+        transfers: BTreeMap::from([(2, 1)]),
+        withhold_votes: 1, abstain_votes: 1, exhausted_votes: 0
+    })]));
+    assert_eq!(first_round.inactive_ballots, InactiveBallots {
+        withhold_votes: 0, abstain_votes: 1, exhausted_votes: 0
+    });
+    assert_eq!(result.rounds[1].elimination_reason, None);
+    assert_eq!(result.rounds[1].inactive_ballots.total(), 3);
+
+    // 3 and 4 tie for the fewest votes, but 3 is also ranked second
+    let raw_votes = vec![
+        vec![1], vec![1], vec![1], vec![2], vec![2], vec![2], vec![3, 1],
+        vec![4, 3]
+    ];
+    let mut rcv = RankedChoiceVoteTrie::new();
+    rcv.insert_votes(RankedVote::from_vectors(&raw_votes).unwrap());
+    let result = rcv.tabulate();
+    assert_eq!(result.rounds[0].eliminated, vec![4]);
+    assert_eq!(
+        result.rounds[0].elimination_reason,
+        Some(EliminationReasons::LowestDowdallScore)
+    );
+    assert_eq!(result.rounds[1].transfers[&3].exhausted_votes, 1);
+    assert_eq!(result.rounds[1].inactive_ballots.exhausted_votes, 0);
+    assert_eq!(result.rounds[2].inactive_ballots.exhausted_votes, 1);
+}
+
+#[test]
+fn test_report_json() {
+    let result = build_trie().tabulate();
+    let report = report_json(&result, candidate_name);
+    assert_eq!(report, report_json(&result.clone(), candidate_name));
+
+    let json: serde_json::Value = serde_json::from_str(&report).unwrap();
+    assert_eq!(json["winner"], "Bob");
+    let first_round = &json["rounds"][0];
+    assert_eq!(first_round["tallies"][0], serde_json::json!({
+        "candidate": "Bob", "votes": 5
+    }));
+    assert_eq!(first_round["elimination_reason"], "fewest_votes");
+    assert_eq!(first_round["eliminated"][0], serde_json::json!({
+        "candidate": "Carol <C|D>",
+        "transfers": [{"candidate": "Bob", "votes": 1}],
+        "withhold": 1, "abstain": 1, "exhausted": 0
+    }));
+    assert_eq!(json["rounds"][1]["inactive_ballots"], serde_json::json!({
+        "withhold": 1, "abstain": 2, "exhausted": 0, "total": 3
+    }));
+    assert_eq!(json["rounds"][1]["elimination_reason"], serde_json::Value::Null);
+}
+
+#[test]
+fn test_report_tables() {
+    let result = build_trie().tabulate();
+    assert_eq!(report_markdown(&result, candidate_name), "\
+**Winner:** Bob
+
+### Rounds
+
+| Candidate | Round 1 | Round 2 |
+| --- | ---: | ---: |
+| Bob | 5 | 6 (+1) |
+| Alice | 4 | 4 |
+| Carol \\<C\\|D\\> | 3 |  |
+| Withheld | 0 | 1 |
+| Abstained | 1 | 2 |
+| Exhausted | 0 | 0 |
+
+### Eliminations
+
+| Round | Eliminated | Reason | Transfers | Withheld | Abstained | Exhausted |
+| ---: | --- | --- | --- | ---: | ---: | ---: |
+| 1 | Carol \\<C\\|D\\> | fewest votes | Bob: 1 | 1 | 1 | 0 |
+");
+
+    let html = report_html(&result, candidate_name);
+    assert!(html.starts_with("<section class=\"rcv-report\">\n"));
+    assert!(html.contains("<p><strong>Winner:</strong> Bob</p>"));
+    assert!(html.contains(
+        "<tr><td>Carol &lt;C|D&gt;</td><td class=\"number\">3</td>\
+        <td class=\"number\"></td></tr>"
+    ));
+    assert!(html.contains("<td>fewest votes</td><td>Bob: 1</td>"));
+}
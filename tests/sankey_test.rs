@@ -0,0 +1,141 @@
+use trie_rcv::{EliminationStrategies, RankedChoiceVoteTrie, TrieTabulation};
+use trie_rcv::sankey::{
+    SankeyDiagram, SankeyLink, SankeyLinkKinds, SankeyNode, SankeyNodeKinds
+};
+use trie_rcv::vote::{RankedVote, SpecialVotes};
+
+const WITHOLD_VOTE_VAL: i32 = SpecialVotes::WITHHOLD.to_int();
+const ABSTAIN_VOTE_VAL: i32 = SpecialVotes::ABSTAIN.to_int();
+
+fn build_trie() -> RankedChoiceVoteTrie {
+    let mut raw_votes = vec![vec![1]; 4];
+    raw_votes.extend(vec![vec![2, 1]; 2]);
+    raw_votes.extend(vec![vec![2]; 3]);
+    raw_votes.extend([
+        vec![3, 2], vec![3, WITHOLD_VOTE_VAL], vec![3, ABSTAIN_VOTE_VAL],
+        vec![ABSTAIN_VOTE_VAL]
+    ]);
+    let mut rcv = RankedChoiceVoteTrie::new();
+    rcv.insert_votes(RankedVote::from_vectors(&raw_votes).unwrap());
+    rcv
+}
+
+fn candidate_name(candidate: &u32) -> String {
+    ["", "Alice", "Bob", "Carol & Co"][*candidate as usize].to_string()
+}
+
+fn assert_flows_balance(diagram: &SankeyDiagram) {
+    // every node after the first round is made up of its incoming links
+    for (index, node) in diagram.nodes.iter().enumerate() {
+        if node.round == 1 { continue }
+        let incoming_votes: u64 = diagram.links.iter()
+            .filter(|link| link.target == index)
+            .map(|link| link.value).sum();
+        assert_eq!(incoming_votes, node.value, "{:?}", node);
+    }
+}
+
+#[test]
+fn test_sankey_nodes_and_links() {
+    let result = build_trie().tabulate();
+    let diagram = SankeyDiagram::from_result(&result, candidate_name);
+    let nodes: Vec<(&str, usize, SankeyNodeKinds, u64)> = diagram.nodes.iter()
+        .map(|node| (node.name.as_str(), node.round, node.kind, node.value))
+        .collect();
+    assert_eq!(nodes, vec![
+        ("Bob", 1, SankeyNodeKinds::Candidate, 5),
+        ("Alice", 1, SankeyNodeKinds::Candidate, 4),
+        ("Carol & Co", 1, SankeyNodeKinds::Candidate, 3),
+        ("Abstained", 1, SankeyNodeKinds::Abstain, 1),
+        ("Bob", 2, SankeyNodeKinds::Candidate, 6),
+        ("Alice", 2, SankeyNodeKinds::Candidate, 4),
+        ("Withheld", 2, SankeyNodeKinds::Withhold, 1),
+        ("Abstained", 2, SankeyNodeKinds::Abstain, 2)
+    ]);
+
+    let transfer = |source, target, value| SankeyLink {
+        source, target, value, kind: SankeyLinkKinds::Transfer
+    };
+    let transfers: Vec<&SankeyLink> = diagram.links.iter()
+        .filter(|link| link.kind == SankeyLinkKinds::Transfer)
+        .collect();
+    assert_eq!(transfers, vec![
+        &transfer(2, 4, 1), &transfer(2, 6, 1), &transfer(2, 7, 1)
+    ]);
+    assert_flows_balance(&diagram);
+
+    let json: serde_json::Value = serde_json::from_str(&diagram.to_json()).unwrap();
+    assert_eq!(json["nodes"].as_array().unwrap().len(), 8);
+    assert_eq!(json["nodes"][3], serde_json::json!({
+        "name": "Abstained", "round": 1, "kind": "abstain", "value": 1
+    }));
+    assert_eq!(json["links"][0], serde_json::json!({
+        "source": 1, "target": 5, "value": 4, "kind": "continuing"
+    }));
+}
+
+#[test]
+fn test_sankey_final_eliminations() {
+    // everyone left is eliminated at once, so their ballots exhaust
+    // in an extra column
+    let raw_votes = vec![vec![1], vec![2], vec![2, 1], vec![1, WITHOLD_VOTE_VAL]];
+    let mut rcv = RankedChoiceVoteTrie::new();
+    rcv.set_elimination_strategy(EliminationStrategies::EliminateAll);
+    rcv.insert_votes(RankedVote::from_vectors(&raw_votes).unwrap());
+    let result = rcv.tabulate();
+    assert_eq!(result.rounds.len(), 1);
+
+    let diagram = SankeyDiagram::from_result(&result, candidate_name);
+    let final_nodes: Vec<(&str, u64)> = diagram.nodes.iter()
+        .filter(|node| node.round == 2)
+        .map(|node| (node.name.as_str(), node.value))
+        .collect();
+    assert_eq!(final_nodes, vec![("Withheld", 1), ("Exhausted", 3)]);
+    assert_flows_balance(&diagram);
+}
+
+#[test]
+fn test_sankey_svg() {
+    let result = build_trie().tabulate();
+    let diagram = SankeyDiagram::from_result(&result, candidate_name);
+    let svg = diagram.to_svg();
+    assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+    assert!(svg.ends_with("</svg>\n"));
+    assert_eq!(svg.matches("<path ").count(), diagram.links.len());
+    assert_eq!(svg.matches("<rect ").count(), diagram.nodes.len());
+    assert!(svg.contains(">Carol &amp; Co: 3</text>"));
+    assert!(svg.contains(">Round 2</text>"));
+    // no scripts or external resources
+    assert!(!svg.contains("<script") && !svg.contains("href"));
+
+    assert_eq!(SankeyDiagram::from_result(&trie_rcv::ElectionResult::<u32> {
+        winner: None, rounds: vec![], excluded_candidates: vec![]
+    }, candidate_name), SankeyDiagram::default());
+}
+
+#[test]
+fn test_sankey_svg_malformed_diagram() {
+    // two different candidates named Alex, a node with no round and a
+    // link to a node that doesn't exist
+    let node = |round: usize, value: u64| SankeyNode {
+        name: "Alex".to_string(), round, kind: SankeyNodeKinds::Candidate, value
+    };
+    let link = |source: usize, target: usize| SankeyLink {
+        source, target, value: 2, kind: SankeyLinkKinds::Continuing
+    };
+    let diagram = SankeyDiagram {
+        nodes: vec![node(1, 3), node(1, 2), node(2, 3), node(2, 2), node(0, 1)],
+        links: vec![link(0, 2), link(1, 3), link(0, 4), link(1, 99)]
+    };
+    let svg = diagram.to_svg();
+    assert_eq!(svg.matches("<path ").count(), 2);
+    assert_eq!(svg.matches("<rect ").count(), 4);
+
+    let fills: Vec<&str> = svg.lines()
+        .filter(|line| line.starts_with("<rect "))
+        .map(|line| line.split("fill=\"").nth(1).unwrap().split('"').next().unwrap())
+        .collect();
+    assert_ne!(fills[0], fills[1]);
+    assert_eq!(fills[0], fills[2]);
+    assert_eq!(fills[1], fills[3]);
+}
@@ -0,0 +1,92 @@
+#![cfg(feature = "serde")]
+use trie_rcv::{
+    ElectionResult, EliminationStrategies, RankedChoiceVoteTrie, TrieTabulation
+};
+use trie_rcv::vote::{RankedVote, SpecialVotes, VoteValues};
+
+#[test]
+fn test_special_votes_serialize_as_ints() {
+    assert_eq!(
+        serde_json::to_string(&SpecialVotes::WITHHOLD).unwrap(),
+        SpecialVotes::WITHHOLD.to_int().to_string()
+    );
+    let abstain_vote: SpecialVotes = serde_json::from_str("-2").unwrap();
+    assert_eq!(abstain_vote, SpecialVotes::ABSTAIN);
+    assert!(serde_json::from_str::<SpecialVotes>("-3").is_err());
+
+    let vote_value: VoteValues =
+        serde_json::from_str(r#"{"SpecialVote": -1}"#).unwrap();
+    assert_eq!(vote_value, VoteValues::SpecialVote(SpecialVotes::WITHHOLD));
+}
+
+#[test]
+fn test_ranked_vote_json_round_trip() {
+    let vote = RankedVote::from_vector(
+        &vec![3, 1, SpecialVotes::ABSTAIN.to_int()]
+    ).unwrap();
+    let json = serde_json::to_string(&vote).unwrap();
+    assert_eq!(json, r#"{"rankings":[3,1],"special_vote":-2}"#);
+    assert_eq!(serde_json::from_str::<RankedVote>(&json).unwrap(), vote);
+
+    // deserialized votes are validated the same way as constructed ones
+    let duplicate_vote_json = r#"{"rankings":[3,3],"special_vote":null}"#;
+    assert!(serde_json::from_str::<RankedVote>(duplicate_vote_json).is_err());
+}
+
+#[test]
+fn test_trie_json_round_trip() {
+    let votes = RankedVote::from_vectors(&vec![
+        vec![1, 2, 3, 4],
+        vec![1, 2, 3],
+        vec![3],
+        vec![3, 2, 4],
+        vec![4, 1, SpecialVotes::WITHHOLD.to_int()]
+    ]).unwrap();
+
+    let mut rcv = RankedChoiceVoteTrie::new();
+    rcv.set_elimination_strategy(EliminationStrategies::RankedPairs);
+    rcv.insert_votes(votes);
+    let json = serde_json::to_string(&rcv).unwrap();
+    let loaded_rcv: RankedChoiceVoteTrie = serde_json::from_str(&json).unwrap();
+    assert_eq!(loaded_rcv.get_num_votes(), rcv.get_num_votes());
+
+    let result = rcv.tabulate();
+    assert_eq!(loaded_rcv.tabulate(), result);
+    let result_json = serde_json::to_string(&result).unwrap();
+    let loaded_result: ElectionResult<u32> =
+        serde_json::from_str(&result_json).unwrap();
+    assert_eq!(loaded_result, result);
+}
+
+#[test]
+fn test_trie_json_rejects_inconsistent_votes() {
+    let mut rcv = RankedChoiceVoteTrie::new();
+    rcv.insert_vote(RankedVote::from_vector(&vec![1]).unwrap());
+    let json = serde_json::to_string(&rcv).unwrap();
+    let is_valid = |json: &str| {
+        serde_json::from_str::<RankedChoiceVoteTrie>(json).is_ok()
+    };
+    assert!(is_valid(&json));
+
+    let child_json = r#"{"children":[],"num_votes":1}"#;
+    let rank_counts_json = r#""rank_counts":{"1":[1]}"#;
+    // a child with no votes would fail tabulation later on
+    let empty_child_json =
+        json.replace(child_json, r#"{"children":[],"num_votes":0}"#);
+    assert!(!is_valid(&empty_child_json));
+    // as would a child with more votes than its parent
+    let bigger_child_json = json
+        .replace(child_json, r#"{"children":[],"num_votes":5}"#)
+        .replace(rank_counts_json, r#""rank_counts":{"1":[5]}"#);
+    assert!(!is_valid(&bigger_child_json));
+
+    let wrong_counts_json =
+        json.replace(rank_counts_json, r#""rank_counts":{"1":[2]}"#);
+    assert!(!is_valid(&wrong_counts_json));
+    let child_pair_json = format!(r#"[{{"Candidate":1}},{child_json}]"#);
+    let duplicate_child_json = json.replace(
+        &child_pair_json, &format!("{child_pair_json},{child_pair_json}")
+    );
+    assert_ne!(duplicate_child_json, json);
+    assert!(!is_valid(&duplicate_child_json));
+}